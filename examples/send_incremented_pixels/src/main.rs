@@ -2,24 +2,37 @@ extern crate rusb;
 
 use dj_screen::vendor::traktor::kontrol_s4_mk3::KontrolS4MK3Driver;
 use dj_screen::vendor::{Driver, ScreenHandle};
-use dj_screen::{Color, IntoPixelIter};
+use dj_screen::{Color, ColorMode, IntoPixelIter};
 use std::time::Duration;
 
 struct CounterColorBGR(u16);
 
+/// Frame whose pixels hold their index, stored as the BGR565 big endian bytes sent to the device.
 struct CounterBufferBGR {
-    init: u16,
+    width: u16,
+    bytes: Vec<u8>,
 }
 
-struct CounterBGRIterator {
-    value: u16,
-    max: usize,
-    pos: usize,
+struct CounterBGRIterator<'a> {
+    buffer: &'a CounterBufferBGR,
+    left: u16,
+    right: u16,
+    bottom: u16,
+    x: u16,
+    y: u16,
 }
 
 impl CounterBufferBGR {
-    pub const fn new() -> CounterBufferBGR {
-        CounterBufferBGR { init: 0 }
+    pub fn new(width: u16, height: u16) -> CounterBufferBGR {
+        let bytes = (0..width as usize * height as usize)
+            .flat_map(|index| (index as u16).to_be_bytes())
+            .collect();
+
+        CounterBufferBGR { width, bytes }
+    }
+
+    fn offset(&self, x: u16, y: u16) -> usize {
+        2 * (y as usize * self.width as usize + x as usize)
     }
 }
 
@@ -29,51 +42,61 @@ impl Color for CounterColorBGR {
     fn components(self) -> [Self::Component; 3] {
         let r = (self.0 << 3) as u8 & 0xF8;
         let g = (self.0 >> 3) as u8 & 0xFC;
-        let b = (self.0 >> 9) as u8 & 0xF8;
+        let b = (self.0 >> 8) as u8 & 0xF8;
 
         [r, g, b]
     }
-
-    fn into_bgr565(self) -> u16 {
-        self.0
-    }
 }
 
-impl IntoPixelIter for &CounterBufferBGR {
-    type IntoIter = CounterBGRIterator;
+impl<'a> IntoPixelIter for &'a CounterBufferBGR {
+    type IntoIter = CounterBGRIterator<'a>;
     type Item = CounterColorBGR;
 
-    fn into_pixel_iter(self, _x: u16, _y: u16, width: u16, height: u16) -> Self::IntoIter {
-        let size = width as usize * height as usize;
-
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
         CounterBGRIterator {
-            value: self.init,
-            max: size,
-            pos: 0,
+            buffer: self,
+            left: x,
+            right: x + width,
+            bottom: y + height,
+            x,
+            y,
         }
     }
+
+    // The frame is already stored as sent to the device, so its rows are copied as is
+    fn native_mode(&self) -> Option<ColorMode> {
+        Some(ColorMode::BGR565BE)
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        let offset = self.offset(x, y);
+
+        self.bytes.get(offset..offset + 2 * width as usize)
+    }
 }
 
-impl Iterator for CounterBGRIterator {
+impl<'a> Iterator for CounterBGRIterator<'a> {
     type Item = CounterColorBGR;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos < self.max {
-            self.pos += 1;
+        if self.left == self.right || self.y >= self.bottom {
+            return None;
+        }
 
-            let value = self.value;
+        let offset = self.buffer.offset(self.x, self.y);
+        let bytes = self.buffer.bytes.get(offset..offset + 2)?;
 
-            self.value = self.value.overflowing_add(1).0;
+        self.x += 1;
 
-            Some(CounterColorBGR(value))
-        } else {
-            None
+        if self.x == self.right {
+            self.x = self.left;
+            self.y += 1;
         }
+
+        Some(CounterColorBGR(u16::from_be_bytes([bytes[0], bytes[1]])))
     }
 }
 
-const COUNTER_BUFFER: &'static CounterBufferBGR = &CounterBufferBGR::new();
-
 fn main() {
     let handle = rusb::open_device_with_vid_pid(0x17cc, 0x1720).unwrap();
 
@@ -82,14 +105,16 @@ fn main() {
     let mut screen1 = driver.acquire_screen(0).unwrap();
     let mut screen2 = driver.acquire_screen(1).unwrap();
 
+    let counter_buffer = CounterBufferBGR::new(screen1.width(), screen1.height());
+
     screen1
-        .send_buffer(COUNTER_BUFFER, 0, 0, screen1.width(), screen1.height())
+        .send_buffer(&counter_buffer, 0, 0, screen1.width(), screen1.height())
         .unwrap();
 
     std::thread::sleep(Duration::from_millis(50));
 
     screen2
-        .send_buffer(COUNTER_BUFFER, 0, 0, screen2.width(), screen2.height())
+        .send_buffer(&counter_buffer, 0, 0, screen2.width(), screen2.height())
         .unwrap();
 
     std::thread::park();
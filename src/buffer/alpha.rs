@@ -0,0 +1,315 @@
+use super::{Color, Component, IntoPixelIter};
+use crate::CoreResult;
+
+/// Storage of the color components of an [`AlphaColor`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// The components are independent of the alpha.
+    #[default]
+    Straight,
+    /// The components are already multiplied by the alpha.
+    Premultiplied,
+}
+
+/// Operation applied between a translucent color and the background it covers.
+///
+/// The result of the operation is then mixed with the background according to the alpha of the
+/// color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Puts the color over the background.
+    #[default]
+    SourceOver,
+    /// Adds the color to the background, saturating at white.
+    Add,
+    /// Multiplies the color and the background, which always darkens the background.
+    Multiply,
+    /// Multiplies the complements of the color and the background, which always lightens the
+    /// background.
+    Screen,
+}
+
+/// Color with an alpha channel.
+///
+/// When used directly as a [`Color`], the alpha channel is ignored. The translucent colors are
+/// meant to be blended over a background, with [`AlphaColor::blend`] or [`Composite`].
+pub trait AlphaColor: Color {
+    const ALPHA_MODE: AlphaMode = AlphaMode::Straight;
+
+    /// Gives the red, green, blue and alpha components of the color.
+    fn components_with_alpha(self) -> [Self::Component; 4];
+
+    /// Blends the color over an opaque background.
+    fn blend<B: Color>(self, background: B, mode: BlendMode) -> BlendedColor {
+        let [red, green, blue, alpha] = self.components_with_alpha();
+        let alpha = alpha.to_u16() as u32;
+        let source = [red, green, blue].map(|component| component.to_u16() as u32);
+        let source = match Self::ALPHA_MODE {
+            AlphaMode::Straight => source,
+            AlphaMode::Premultiplied => source.map(|component| unpremultiply(component, alpha)),
+        };
+        let backdrop = background
+            .components()
+            .map(|component| component.to_u16() as u32);
+
+        BlendedColor(std::array::from_fn(|i| {
+            mode.blend(backdrop[i], source[i], alpha)
+        }))
+    }
+}
+
+/// Opaque color resulting of a blending, with components on 16 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlendedColor(pub [u16; 3]);
+
+/// Pixel source compositing a translucent source over an opaque background.
+///
+/// Both sources are read on the same area.
+#[derive(Clone, Copy, Debug)]
+pub struct Composite<F, B> {
+    foreground: F,
+    background: B,
+    mode: BlendMode,
+}
+
+pub struct CompositeIter<F, B> {
+    foreground: F,
+    background: B,
+    mode: BlendMode,
+}
+
+const MAX: u32 = 0xFFFF;
+
+/// Multiplies two values scaled on 16 bits.
+const fn multiply(first: u32, second: u32) -> u32 {
+    (first * second + MAX / 2) / MAX
+}
+
+/// Divides a premultiplied component by its alpha, both scaled on 16 bits.
+const fn unpremultiply(component: u32, alpha: u32) -> u32 {
+    match (component * MAX + alpha / 2).checked_div(alpha) {
+        None => 0,
+        Some(component) if component > MAX => MAX,
+        Some(component) => component,
+    }
+}
+
+impl BlendMode {
+    /// Blends a straight component over a backdrop component, both scaled on 16 bits.
+    pub const fn blend(&self, backdrop: u32, source: u32, alpha: u32) -> u16 {
+        let mixed = match self {
+            BlendMode::SourceOver => source,
+            BlendMode::Add => {
+                if backdrop + source > MAX {
+                    MAX
+                } else {
+                    backdrop + source
+                }
+            }
+            BlendMode::Multiply => multiply(backdrop, source),
+            BlendMode::Screen => backdrop + source - multiply(backdrop, source),
+        };
+
+        (multiply(backdrop, MAX - alpha) + multiply(mixed, alpha)) as u16
+    }
+}
+
+impl Color for BlendedColor {
+    type Component = u16;
+
+    fn components(self) -> [u16; 3] {
+        self.0
+    }
+}
+
+impl<F, B> Composite<F, B> {
+    pub const fn new(foreground: F, background: B, mode: BlendMode) -> Composite<F, B> {
+        Composite {
+            foreground,
+            background,
+            mode,
+        }
+    }
+}
+
+impl<F, B> IntoPixelIter for Composite<F, B>
+where
+    F: IntoPixelIter,
+    F::Item: AlphaColor,
+    B: IntoPixelIter,
+{
+    type IntoIter = CompositeIter<F::IntoIter, B::IntoIter>;
+    type Item = BlendedColor;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        CompositeIter {
+            foreground: self.foreground.into_pixel_iter(x, y, width, height),
+            background: self.background.into_pixel_iter(x, y, width, height),
+            mode: self.mode,
+        }
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        self.foreground.check_area(x, y, width, height)?;
+        self.background.check_area(x, y, width, height)
+    }
+}
+
+impl<F, B> Iterator for CompositeIter<F, B>
+where
+    F: Iterator,
+    F::Item: AlphaColor,
+    B: Iterator,
+    B::Item: Color,
+{
+    type Item = BlendedColor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let foreground = self.foreground.next()?;
+        let background = self.background.next()?;
+
+        Some(foreground.blend(background, self.mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlphaColor, AlphaMode, BlendMode, BlendedColor, Composite};
+    use crate::test_helper::{DummyBuffer, COLOR_BLUE, COLOR_WHITE};
+    use crate::{Buffer, Color, IntoPixelIter};
+
+    struct Straight([u8; 4]);
+
+    struct Premultiplied([u8; 4]);
+
+    impl Color for Straight {
+        type Component = u8;
+
+        fn components(self) -> [u8; 3] {
+            [self.0[0], self.0[1], self.0[2]]
+        }
+    }
+
+    impl AlphaColor for Straight {
+        fn components_with_alpha(self) -> [u8; 4] {
+            self.0
+        }
+    }
+
+    impl Color for Premultiplied {
+        type Component = u8;
+
+        fn components(self) -> [u8; 3] {
+            [self.0[0], self.0[1], self.0[2]]
+        }
+    }
+
+    impl AlphaColor for Premultiplied {
+        const ALPHA_MODE: AlphaMode = AlphaMode::Premultiplied;
+
+        fn components_with_alpha(self) -> [u8; 4] {
+            self.0
+        }
+    }
+
+    struct Overlay(usize);
+
+    impl IntoPixelIter for Overlay {
+        type IntoIter = std::iter::Map<std::ops::Range<usize>, fn(usize) -> Straight>;
+        type Item = Straight;
+
+        fn into_pixel_iter(self, _x: u16, _y: u16, width: u16, height: u16) -> Self::IntoIter {
+            let length = width as usize * height as usize;
+
+            (self.0..self.0 + length).map(|i| Straight([0xFF, 0x00, 0x00, (i * 0x55) as u8]))
+        }
+    }
+
+    fn to_rgb(color: BlendedColor) -> [u8; 3] {
+        let mut buffer = [0u8; 3];
+
+        color.fill_rgb(&mut buffer).unwrap();
+
+        buffer
+    }
+
+    #[test]
+    fn test_source_over() {
+        let red = Straight([0xFF, 0x00, 0x00, 0x80]);
+
+        assert_eq!(
+            to_rgb(red.blend(&COLOR_BLUE, BlendMode::SourceOver)),
+            [0x80, 0x00, 0x7F]
+        );
+
+        let opaque = Straight([0x12, 0x34, 0x56, 0xFF]);
+
+        assert_eq!(
+            to_rgb(opaque.blend(&COLOR_BLUE, BlendMode::SourceOver)),
+            [0x12, 0x34, 0x56]
+        );
+
+        let transparent = Straight([0x12, 0x34, 0x56, 0x00]);
+
+        assert_eq!(
+            to_rgb(transparent.blend(&COLOR_BLUE, BlendMode::SourceOver)),
+            [0x00, 0x00, 0xFF]
+        );
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let straight = Straight([0xFF, 0x80, 0x00, 0x80]);
+        let premultiplied = Premultiplied([0x80, 0x40, 0x00, 0x80]);
+
+        for mode in [
+            BlendMode::SourceOver,
+            BlendMode::Add,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+        ] {
+            let straight = to_rgb(Straight(straight.0).blend(&COLOR_BLUE, mode));
+            let premultiplied = to_rgb(Premultiplied(premultiplied.0).blend(&COLOR_BLUE, mode));
+
+            for (straight, premultiplied) in straight.into_iter().zip(premultiplied) {
+                assert!(straight.abs_diff(premultiplied) <= 1, "{mode:?}");
+            }
+        }
+
+        // The transparent colors have no color
+        assert_eq!(
+            to_rgb(Premultiplied([0, 0, 0, 0]).blend(&COLOR_WHITE, BlendMode::SourceOver)),
+            [0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let gray = BlendedColor([0x8080; 3]);
+        let blend = |alpha, mode| to_rgb(Straight([0xFF, 0x80, 0x00, alpha]).blend(gray, mode));
+
+        assert_eq!(blend(0xFF, BlendMode::SourceOver), [0xFF, 0x80, 0x00]);
+        assert_eq!(blend(0xFF, BlendMode::Add), [0xFF, 0xFF, 0x80]);
+        assert_eq!(blend(0xFF, BlendMode::Multiply), [0x80, 0x40, 0x00]);
+        assert_eq!(blend(0xFF, BlendMode::Screen), [0xFF, 0xC0, 0x80]);
+
+        assert_eq!(blend(0x00, BlendMode::Add), [0x80, 0x80, 0x80]);
+        assert_eq!(blend(0x80, BlendMode::Multiply), [0x80, 0x60, 0x40]);
+        assert_eq!(blend(0x80, BlendMode::Screen), [0xC0, 0xA0, 0x80]);
+    }
+
+    #[test]
+    fn test_composite() {
+        let background = DummyBuffer::new(COLOR_BLUE);
+        let mut buffer = [0u8; 12];
+
+        Composite::new(Overlay(0), &background, BlendMode::SourceOver)
+            .fill_rgb(&mut buffer, 0, 0, 2, 2)
+            .unwrap();
+
+        assert_eq!(
+            buffer,
+            [0x00, 0x00, 0xFF, 0x55, 0x00, 0xAA, 0xAA, 0x00, 0x55, 0xFF, 0x00, 0x00]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::Component;
+use super::{Component, Luminance, Quantizer};
 use crate::error::*;
 
 pub trait Color: Sized {
@@ -7,29 +7,74 @@ pub trait Color: Sized {
     fn components(self) -> [Self::Component; 3];
 
     fn into_rgb565(self) -> u16 {
+        self.into_rgb565_with(Quantizer::default())
+    }
+
+    fn into_bgr565(self) -> u16 {
+        self.into_bgr565_with(Quantizer::default())
+    }
+
+    fn into_rgb565_with(self, quantizer: Quantizer) -> u16 {
         let [red, green, blue] = self.components();
 
-        let r_rgb565 = (red.most_significant_byte() as u16 & 0b11111000) << 8;
-        let g_rgb565 = (green.most_significant_byte() as u16 & 0b11111100) << 3;
-        let b_rgb565 = (blue.most_significant_byte() as u16 & 0b11111000) >> 3;
+        let r_rgb565 = (quantizer.quantize(red, 5) as u16) << 11;
+        let g_rgb565 = (quantizer.quantize(green, 6) as u16) << 5;
+        let b_rgb565 = quantizer.quantize(blue, 5) as u16;
 
         r_rgb565 | g_rgb565 | b_rgb565
     }
 
-    fn into_bgr565(self) -> u16 {
+    fn into_bgr565_with(self, quantizer: Quantizer) -> u16 {
         let [red, green, blue] = self.components();
 
-        let r_rgb565 = (red.most_significant_byte() as u16 & 0b11111000) >> 3;
-        let g_rgb565 = (green.most_significant_byte() as u16 & 0b11111100) << 3;
-        let b_rgb565 = (blue.most_significant_byte() as u16 & 0b11111000) << 8;
+        let r_rgb565 = quantizer.quantize(red, 5) as u16;
+        let g_rgb565 = (quantizer.quantize(green, 6) as u16) << 5;
+        let b_rgb565 = (quantizer.quantize(blue, 5) as u16) << 11;
 
         r_rgb565 | g_rgb565 | b_rgb565
     }
 
+    fn into_rgb444(self) -> u16 {
+        self.into_rgb444_with(Quantizer::default())
+    }
+
+    fn into_rgb332(self) -> u8 {
+        self.into_rgb332_with(Quantizer::default())
+    }
+
+    /// Gives the color on the 12 least significant bits, the red component being on the most
+    /// significant ones.
+    fn into_rgb444_with(self, quantizer: Quantizer) -> u16 {
+        let [red, green, blue] = self.components();
+
+        let r_rgb444 = (quantizer.quantize(red, 4) as u16) << 8;
+        let g_rgb444 = (quantizer.quantize(green, 4) as u16) << 4;
+        let b_rgb444 = quantizer.quantize(blue, 4) as u16;
+
+        r_rgb444 | g_rgb444 | b_rgb444
+    }
+
+    fn into_rgb332_with(self, quantizer: Quantizer) -> u8 {
+        let [red, green, blue] = self.components();
+
+        let r_rgb332 = quantizer.quantize(red, 3) << 5;
+        let g_rgb332 = quantizer.quantize(green, 3) << 2;
+        let b_rgb332 = quantizer.quantize(blue, 2);
+
+        r_rgb332 | g_rgb332 | b_rgb332
+    }
+
     fn luminance(self) -> Self::Component {
+        self.luminance_with(Luminance::default())
+    }
+
+    fn luminance_with(self, model: Luminance) -> Self::Component {
         let [red, green, blue] = self.components();
 
-        Self::Component::max_component(red, green, blue)
+        match model.weights() {
+            Some(weights) => red.weighted_mean(green, blue, weights),
+            None => red.max_component(green, blue),
+        }
     }
 
     fn fill_rgb(self, buf: &mut [u8]) -> CoreResult<()> {
@@ -37,9 +82,9 @@ pub trait Color: Sized {
 
         let [red, green, blue] = self.components();
 
-        buf[0] = red.most_significant_byte();
-        buf[1] = green.most_significant_byte();
-        buf[2] = blue.most_significant_byte();
+        buf[0] = red.to_u8();
+        buf[1] = green.to_u8();
+        buf[2] = blue.to_u8();
 
         Ok(())
     }
@@ -48,83 +93,181 @@ pub trait Color: Sized {
 
         let [red, green, blue] = self.components();
 
-        buf[0] = blue.most_significant_byte();
-        buf[1] = green.most_significant_byte();
-        buf[2] = red.most_significant_byte();
+        buf[0] = blue.to_u8();
+        buf[1] = green.to_u8();
+        buf[2] = red.to_u8();
 
         Ok(())
     }
     fn fill_rgb565le(self, buf: &mut [u8]) -> CoreResult<()> {
-        self.into_rgb565().convert_le(buf)
+        self.fill_rgb565le_with(Quantizer::default(), buf)
     }
     fn fill_rgb565be(self, buf: &mut [u8]) -> CoreResult<()> {
-        self.into_rgb565().convert_be(buf)
+        self.fill_rgb565be_with(Quantizer::default(), buf)
     }
     fn fill_bgr565le(self, buf: &mut [u8]) -> CoreResult<()> {
-        self.into_bgr565().convert_le(buf)
+        self.fill_bgr565le_with(Quantizer::default(), buf)
     }
     fn fill_bgr565be(self, buf: &mut [u8]) -> CoreResult<()> {
-        self.into_bgr565().convert_be(buf)
+        self.fill_bgr565be_with(Quantizer::default(), buf)
+    }
+    fn fill_rgb565le_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        self.into_rgb565_with(quantizer).convert_le(buf)
+    }
+    fn fill_rgb565be_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        self.into_rgb565_with(quantizer).convert_be(buf)
+    }
+    fn fill_bgr565le_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        self.into_bgr565_with(quantizer).convert_le(buf)
+    }
+    fn fill_bgr565be_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        self.into_bgr565_with(quantizer).convert_be(buf)
+    }
+    /// Fills a 32 bits pixel, the first byte being unused.
+    ///
+    /// The unused byte is set to `0xFF`, so the pixel stays opaque when read as ARGB.
+    fn fill_xrgb8888(self, buf: &mut [u8]) -> CoreResult<()> {
+        CoreError::check_length(buf, 4)?;
+
+        buf[0] = 0xFF;
+        self.fill_rgb(&mut buf[1..])
+    }
+    fn fill_rgba8888(self, buf: &mut [u8]) -> CoreResult<()> {
+        CoreError::check_length(buf, 4)?;
+
+        buf[3] = 0xFF;
+        self.fill_rgb(&mut buf[..3])
+    }
+    fn fill_rgb666(self, buf: &mut [u8]) -> CoreResult<()> {
+        self.fill_rgb666_with(Quantizer::default(), buf)
+    }
+    fn fill_rgb444(from: [Self; 2], buf: &mut [u8]) -> CoreResult<()> {
+        Self::fill_rgb444_with(from, Quantizer::default(), buf)
+    }
+    fn fill_rgb332(self, buf: &mut [u8]) -> CoreResult<()> {
+        self.fill_rgb332_with(Quantizer::default(), buf)
+    }
+    /// Fills a pixel using 6 bits per component, each component being stored on the most
+    /// significant bits of its own byte.
+    fn fill_rgb666_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        CoreError::check_length(buf, 3)?;
+
+        for (byte, component) in buf.iter_mut().zip(self.components()) {
+            *byte = quantizer.quantize(component, 6) << 2;
+        }
+
+        Ok(())
+    }
+    /// Fills two pixels using 12 bits each, the first pixel being on the most significant bits.
+    fn fill_rgb444_with(from: [Self; 2], quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        CoreError::check_length(buf, 3)?;
+
+        let [first, second] = from.map(|pixel| pixel.into_rgb444_with(quantizer) as u32);
+
+        buf.copy_from_slice(&(first << 12 | second).to_be_bytes()[1..]);
+
+        Ok(())
+    }
+    fn fill_rgb332_with(self, quantizer: Quantizer, buf: &mut [u8]) -> CoreResult<()> {
+        CoreError::check_length(buf, 1)?;
+
+        buf[0] = self.into_rgb332_with(quantizer);
+
+        Ok(())
     }
     fn fill_grayscale_1bit(from: [Self; 8], buf: &mut [u8]) -> CoreResult<()> {
+        Self::fill_grayscale_1bit_with(from, Luminance::default(), buf)
+    }
+    fn fill_grayscale_2bit(from: [Self; 4], buf: &mut [u8]) -> CoreResult<()> {
+        Self::fill_grayscale_2bit_with(from, Luminance::default(), buf)
+    }
+    fn fill_grayscale_4bit(from: [Self; 2], buf: &mut [u8]) -> CoreResult<()> {
+        Self::fill_grayscale_4bit_with(from, Luminance::default(), buf)
+    }
+    fn fill_grayscale_8bit(self, buf: &mut [u8]) -> CoreResult<()> {
+        self.fill_grayscale_8bit_with(Luminance::default(), buf)
+    }
+
+    fn fill_grayscale_16bit_le(self, buf: &mut [u8]) -> CoreResult<()> {
+        self.fill_grayscale_16bit_le_with(Luminance::default(), buf)
+    }
+
+    fn fill_grayscale_16bit_be(self, buf: &mut [u8]) -> CoreResult<()> {
+        self.fill_grayscale_16bit_be_with(Luminance::default(), buf)
+    }
+
+    fn fill_grayscale_1bit_with(
+        from: [Self; 8],
+        model: Luminance,
+        buf: &mut [u8],
+    ) -> CoreResult<()> {
         CoreError::check_length(buf, 1)?;
 
         buf[0] = 0;
 
         for pixel in from {
-            let data = pixel.luminance().most_significant_byte();
+            let data = pixel.luminance_with(model).scale(1) as u8;
 
-            buf[0] = buf[0] << 1 | data >> 7
+            buf[0] = buf[0] << 1 | data
         }
 
         Ok(())
     }
-    fn fill_grayscale_2bit(from: [Self; 4], buf: &mut [u8]) -> CoreResult<()> {
+    fn fill_grayscale_2bit_with(
+        from: [Self; 4],
+        model: Luminance,
+        buf: &mut [u8],
+    ) -> CoreResult<()> {
         CoreError::check_length(buf, 1)?;
 
         buf[0] = 0;
 
         for pixel in from {
-            let data = pixel.luminance().most_significant_byte();
+            let data = pixel.luminance_with(model).scale(2) as u8;
 
-            buf[0] = buf[0] << 2 | data >> 6;
+            buf[0] = buf[0] << 2 | data;
         }
 
         Ok(())
     }
-    fn fill_grayscale_4bit(from: [Self; 2], buf: &mut [u8]) -> CoreResult<()> {
+    fn fill_grayscale_4bit_with(
+        from: [Self; 2],
+        model: Luminance,
+        buf: &mut [u8],
+    ) -> CoreResult<()> {
         CoreError::check_length(buf, 1)?;
 
         buf[0] = 0;
 
         for pixel in from {
-            let data = pixel.luminance().most_significant_byte();
+            let data = pixel.luminance_with(model).scale(4) as u8;
 
-            buf[0] = buf[0] << 4 | data >> 4;
+            buf[0] = buf[0] << 4 | data;
         }
 
         Ok(())
     }
-    fn fill_grayscale_8bit(self, buf: &mut [u8]) -> CoreResult<()> {
+    fn fill_grayscale_8bit_with(self, model: Luminance, buf: &mut [u8]) -> CoreResult<()> {
         CoreError::check_length(buf, 1)?;
 
-        buf[0] = self.luminance().most_significant_byte();
+        buf[0] = self.luminance_with(model).to_u8();
 
         Ok(())
     }
 
-    fn fill_grayscale_16bit_le(self, _buf: &mut [u8]) -> CoreResult<()> {
-        todo!()
+    fn fill_grayscale_16bit_le_with(self, model: Luminance, buf: &mut [u8]) -> CoreResult<()> {
+        self.luminance_with(model).to_u16().convert_le(buf)
     }
 
-    fn fill_grayscale_16bit_be(self, _buf: &mut [u8]) -> CoreResult<()> {
-        todo!()
+    fn fill_grayscale_16bit_be_with(self, model: Luminance, buf: &mut [u8]) -> CoreResult<()> {
+        self.luminance_with(model).to_u16().convert_be(buf)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod tests {
-    use crate::Color;
+    use crate::{Color, Luminance, Quantizer};
 
     const RED: u8 = 0b10011010;
     const GREEN: u8 = 0b11100111;
@@ -140,6 +283,26 @@ mod tests {
     const COLOR1: RGB888Color = RGB888Color::new(RED, GREEN, BLUE);
     const COLOR2: RGB888Color = RGB888Color::new(GRAYSCALE2, GRAYSCALE2, GRAYSCALE2);
 
+    struct RGB161616Color([u16; 3]);
+
+    impl Color for RGB161616Color {
+        type Component = u16;
+
+        fn components(self) -> [u16; 3] {
+            self.0
+        }
+    }
+
+    struct LinearColor([f32; 3]);
+
+    impl Color for LinearColor {
+        type Component = f32;
+
+        fn components(self) -> [f32; 3] {
+            self.0
+        }
+    }
+
     struct RGB888Color {
         red: u8,
         green: u8,
@@ -210,12 +373,199 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rgb565_quantizers() {
+        let color = || RGB888Color::new(0x84, 0xFF, 0x7C);
+
+        assert_eq!(
+            color().into_rgb565_with(Quantizer::Truncate),
+            0b10000_111111_01111
+        );
+        assert_eq!(
+            color().into_rgb565_with(Quantizer::RoundToNearest),
+            0b10000_111111_01111
+        );
+        assert_eq!(
+            color().into_rgb565_with(Quantizer::RoundWithClamp),
+            0b10001_111111_10000
+        );
+        assert_eq!(
+            RGB888Color::new(0x05, 0x03, 0xFC).into_rgb565_with(Quantizer::RoundToNearest),
+            0b00001_000001_11111
+        );
+        assert_eq!(
+            RGB888Color::new(0x05, 0x03, 0xFC).into_bgr565_with(Quantizer::RoundToNearest),
+            0b11111_000001_00001
+        );
+        assert_eq!(
+            color().into_bgr565_with(Quantizer::RoundWithClamp),
+            0b10000_111111_10001
+        );
+
+        let mut buffer = [0u8; 2];
+
+        color()
+            .fill_bgr565be_with(Quantizer::RoundWithClamp, &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, 0b10000_111111_10001u16.to_be_bytes());
+    }
+
+    #[test]
+    fn test_wide_components() {
+        let mut buffer = [0u8; 3];
+
+        RGB161616Color([0x12FF, 0xFFFF, 0x0000])
+            .fill_rgb(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0x13, 0xFF, 0x00]);
+
+        RGB161616Color([0x12FF, 0xFFFF, 0x0000])
+            .fill_bgr(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0x00, 0xFF, 0x13]);
+
+        assert_eq!(
+            RGB161616Color([0xFFFF, 0x8000, 0x0800]).into_rgb565(),
+            0b11111_100000_00001
+        );
+        assert_eq!(
+            RGB161616Color([0xFFFF, 0x8000, 0x0800]).into_bgr565(),
+            0b00001_100000_11111
+        );
+
+        RGB161616Color([0x8000, 0x8000, 0x8000])
+            .fill_grayscale_8bit(&mut buffer[..1])
+            .unwrap();
+        assert_eq!(buffer[0], 0x80);
+    }
+
+    #[test]
+    fn test_float_components() {
+        let mut buffer = [0u8; 3];
+
+        LinearColor([1.0, 0.5, 0.0]).fill_rgb(&mut buffer).unwrap();
+        assert_eq!(buffer, [0xFF, 0x80, 0x00]);
+
+        LinearColor([2.0, f32::NAN, -1.0])
+            .fill_bgr(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0x00, 0x00, 0xFF]);
+
+        assert_eq!(
+            LinearColor([1.0, 0.5, 0.0]).into_rgb565(),
+            0b11111_100000_00000
+        );
+        assert_eq!(
+            LinearColor([f32::INFINITY, f32::NAN, -0.5]).into_bgr565(),
+            0b00000_000000_11111
+        );
+
+        LinearColor([0.5, 0.25, f32::NAN])
+            .fill_grayscale_8bit(&mut buffer[..1])
+            .unwrap();
+        assert_eq!(buffer[0], 0x80);
+
+        Color::fill_grayscale_1bit(
+            [0.0, 1.0, 0.6, 0.4, 1.5, -1.0, f32::NAN, 1.0].map(|level| LinearColor([level; 3])),
+            &mut buffer[..1],
+        )
+        .unwrap();
+        assert_eq!(buffer[0], 0b0110_1001);
+    }
+
     #[test]
     fn test_luminance() {
         assert_eq!(COLOR1.luminance(), GRAYSCALE1);
         assert_eq!(COLOR2.luminance(), GRAYSCALE2);
     }
 
+    #[test]
+    fn test_luminance_models() {
+        const WHITE: [u8; 3] = [0xFF, 0xFF, 0xFF];
+        const MODELS: [Luminance; 5] = [
+            Luminance::Max,
+            Luminance::Average,
+            Luminance::Rec601,
+            Luminance::Rec709,
+            Luminance::Weighted {
+                red: 1,
+                green: 2,
+                blue: 1,
+            },
+        ];
+
+        // Reference values: round(255 * weight) for each primary
+        const REFERENCES: [[u8; 3]; 5] = [
+            [255, 255, 255],
+            [85, 85, 85],
+            [76, 150, 29],
+            [54, 182, 18],
+            [64, 128, 64],
+        ];
+
+        for (model, reference) in MODELS.into_iter().zip(REFERENCES) {
+            let red = RGB888Color::new(0xFF, 0, 0).luminance_with(model);
+            let green = RGB888Color::new(0, 0xFF, 0).luminance_with(model);
+            let blue = RGB888Color::new(0, 0, 0xFF).luminance_with(model);
+            let [r, g, b] = WHITE;
+
+            assert_eq!([red, green, blue], reference, "{model:?}");
+            assert_eq!(
+                RGB888Color::new(r, g, b).luminance_with(model),
+                0xFF,
+                "{model:?}"
+            );
+        }
+
+        // 0.299 * 0x1234 + 0.587 * 0x0FED + 0.114 * 0x00FF = 3815.609
+        assert_eq!(
+            RGB161616Color([0x1234, 0x0FED, 0x00FF]).luminance_with(Luminance::Rec601),
+            3816
+        );
+        // 0.2126 * 0x1234 + 0.7152 * 0x0FED + 0.0722 * 0x00FF = 3924.9974
+        assert_eq!(
+            RGB161616Color([0x1234, 0x0FED, 0x00FF]).luminance_with(Luminance::Rec709),
+            3925
+        );
+    }
+
+    #[test]
+    fn test_fill_grayscale_with() {
+        let mut buffer = [0u8; 2];
+        let blue = || RGB888Color::new(0, 0, 0xFF);
+
+        blue()
+            .fill_grayscale_8bit_with(Luminance::Rec709, &mut buffer[..1])
+            .unwrap();
+        assert_eq!(buffer[0], 18);
+
+        blue()
+            .fill_grayscale_16bit_be_with(Luminance::Rec601, &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [29, 29]);
+
+        Color::fill_grayscale_1bit_with(
+            [
+                COLOR1,
+                blue(),
+                COLOR1,
+                blue(),
+                COLOR1,
+                blue(),
+                COLOR1,
+                blue(),
+            ],
+            Luminance::Rec709,
+            &mut buffer[..1],
+        )
+        .unwrap();
+        assert_eq!(buffer[0], 0b10101010);
+
+        Color::fill_grayscale_4bit_with([blue(), COLOR1], Luminance::Average, &mut buffer[..1])
+            .unwrap();
+        assert_eq!(buffer[0], 0b0101_1000);
+    }
+
     #[test]
     fn test_fill_rgb() {
         let mut buffer = [0u8; 3];
@@ -314,4 +664,159 @@ mod tests {
 
         assert_eq!(buffer, [GRAYSCALE2]);
     }
+
+    #[test]
+    fn test_fill_grayscale16bit() {
+        let mut buffer = [0u8; 2];
+
+        COLOR1.fill_grayscale_16bit_le(&mut buffer).unwrap();
+        assert_eq!(buffer, [GRAYSCALE1, GRAYSCALE1]);
+
+        RGB888Color::new(0xFF, 0xFF, 0xFF)
+            .fill_grayscale_16bit_be(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0xFF, 0xFF]);
+
+        RGB161616Color([0x1234, 0x0FED, 0x00FF])
+            .fill_grayscale_16bit_le(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0x34, 0x12]);
+
+        RGB161616Color([0x1234, 0x0FED, 0x00FF])
+            .fill_grayscale_16bit_be(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0x12, 0x34]);
+
+        assert!(COLOR1.fill_grayscale_16bit_be(&mut buffer[..1]).is_err());
+    }
+
+    /// Expands a component stored on the given number of bits to 8 bits by replicating its bits.
+    fn expand(level: u8, bits: u32) -> u8 {
+        let mut value = 0u32;
+        let mut filled = 0;
+
+        while filled < 8 {
+            value = value << bits | level as u32;
+            filled += bits;
+        }
+
+        (value >> (filled - 8)) as u8
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand(0b101, 3), 0b101_101_10);
+        assert_eq!(expand(0b11, 2), 0xFF);
+        assert_eq!(expand(0b1010, 4), 0xAA);
+        assert_eq!(expand(0b100000, 6), 0b100000_10);
+    }
+
+    #[test]
+    fn test_fill_xrgb8888() {
+        let mut buffer = [0u8; 4];
+
+        COLOR1.fill_xrgb8888(&mut buffer).unwrap();
+        assert_eq!(buffer, [0xFF, RED, GREEN, BLUE]);
+
+        let mut buffer = [0u8; 3];
+
+        assert!(COLOR1.fill_xrgb8888(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_fill_rgba8888() {
+        let mut buffer = [0u8; 4];
+
+        COLOR1.fill_rgba8888(&mut buffer).unwrap();
+        assert_eq!(buffer, [RED, GREEN, BLUE, 0xFF]);
+
+        let mut buffer = [0u8; 5];
+
+        assert!(COLOR1.fill_rgba8888(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_fill_rgb666() {
+        let mut buffer = [0u8; 3];
+
+        COLOR1.fill_rgb666(&mut buffer).unwrap();
+        assert_eq!(buffer, [0b100110_00, 0b111001_00, 0b001010_00]);
+
+        COLOR1
+            .fill_rgb666_with(Quantizer::RoundWithClamp, &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [0b100111_00, 0b111010_00, 0b001011_00]);
+
+        // Round trip of every level
+        for level in 0..64 {
+            let value = expand(level, 6);
+
+            for quantizer in [Quantizer::Truncate, Quantizer::RoundToNearest] {
+                RGB888Color::new(value, value, value)
+                    .fill_rgb666_with(quantizer, &mut buffer)
+                    .unwrap();
+                assert_eq!(buffer, [level << 2; 3]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_rgb444() {
+        let mut buffer = [0u8; 3];
+
+        RGB888Color::fill_rgb444([COLOR1, COLOR2], &mut buffer).unwrap();
+        assert_eq!(buffer, [0b1001_1110, 0b0010_0100, 0b0100_0100]);
+
+        let mut buffer = [0u8; 2];
+
+        assert!(RGB888Color::fill_rgb444([COLOR1, COLOR2], &mut buffer).is_err());
+
+        // Round trip of every color
+        let mut buffer = [0u8; 3];
+
+        for code in 0..0x1000u16 {
+            let color = || {
+                RGB888Color::new(
+                    expand((code >> 8) as u8, 4),
+                    expand((code >> 4 & 0xF) as u8, 4),
+                    expand((code & 0xF) as u8, 4),
+                )
+            };
+
+            assert_eq!(color().into_rgb444(), code);
+            assert_eq!(color().into_rgb444_with(Quantizer::RoundToNearest), code);
+
+            RGB888Color::fill_rgb444([color(), COLOR2], &mut buffer).unwrap();
+
+            let pixels = u32::from_be_bytes([0, buffer[0], buffer[1], buffer[2]]);
+
+            assert_eq!(pixels >> 12, code as u32);
+            assert_eq!(pixels & 0xFFF, 0x444);
+        }
+    }
+
+    #[test]
+    fn test_fill_rgb332() {
+        let mut buffer = [0u8; 1];
+
+        COLOR1.fill_rgb332(&mut buffer).unwrap();
+        assert_eq!(buffer, [0b100_111_00]);
+
+        // Round trip of every color
+        for code in 0..=0xFFu8 {
+            let color = || {
+                RGB888Color::new(
+                    expand(code >> 5, 3),
+                    expand(code >> 2 & 0b111, 3),
+                    expand(code & 0b11, 2),
+                )
+            };
+
+            assert_eq!(color().into_rgb332(), code);
+            assert_eq!(color().into_rgb332_with(Quantizer::RoundToNearest), code);
+
+            color().fill_rgb332(&mut buffer).unwrap();
+            assert_eq!(buffer, [code]);
+        }
+    }
 }
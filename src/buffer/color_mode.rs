@@ -1,6 +1,12 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ColorMode {
     RGB,
     BGR,
+    XRGB8888,
+    RGBA8888,
+    RGB666,
+    RGB444,
+    RGB332,
     RGB565LE,
     RGB565BE,
     BGR565LE,
@@ -11,11 +17,34 @@ pub enum ColorMode {
     Grayscale8Bit,
     Grayscale16BitLE,
     Grayscale16BitBE,
+    Indexed1Bit,
+    Indexed2Bit,
+    Indexed4Bit,
+    Indexed8Bit,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StorageMode {
     BytesPerPixel(usize),
     PixelsPerByte(usize),
+    /// A group of pixels stored in several bytes.
+    PixelGroup {
+        pixels: usize,
+        bytes: usize,
+    },
+}
+
+/// Layout of the pixels for the color modes storing several pixels in one byte or one group of
+/// bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubpixelPacking {
+    /// Each row begins on a new byte or group. The unused bits at the end of a row are set to
+    /// zero.
+    #[default]
+    RowPadded,
+    /// The pixels are packed one after the other, regardless of the row boundaries. Only the
+    /// last byte or group of the buffer may be padded.
+    Continuous,
 }
 
 impl ColorMode {
@@ -26,12 +55,17 @@ impl ColorMode {
     ///
     /// # Returns
     ///
-    /// If the color needs less than 8 bits, the function returns the bit size of the color.
-    /// Otherwise, the function return the byte size.
+    /// If the color needs less than 8 bits, the function returns the number of pixels stored in one
+    /// byte, or in one group of bytes when the pixels are not aligned on bytes. Otherwise, the
+    /// function return the byte size.
     pub fn byte_size(&self) -> StorageMode {
         match self {
             ColorMode::RGB => StorageMode::BytesPerPixel(3),
             ColorMode::BGR => StorageMode::BytesPerPixel(3),
+            ColorMode::XRGB8888 => StorageMode::BytesPerPixel(4),
+            ColorMode::RGBA8888 => StorageMode::BytesPerPixel(4),
+            ColorMode::RGB666 => StorageMode::BytesPerPixel(3),
+            ColorMode::RGB332 => StorageMode::BytesPerPixel(1),
             ColorMode::RGB565LE => StorageMode::BytesPerPixel(2),
             ColorMode::RGB565BE => StorageMode::BytesPerPixel(2),
             ColorMode::BGR565LE => StorageMode::BytesPerPixel(2),
@@ -39,10 +73,145 @@ impl ColorMode {
             ColorMode::Grayscale8Bit => StorageMode::BytesPerPixel(1),
             ColorMode::Grayscale16BitLE => StorageMode::BytesPerPixel(2),
             ColorMode::Grayscale16BitBE => StorageMode::BytesPerPixel(2),
+            ColorMode::Indexed8Bit => StorageMode::BytesPerPixel(1),
 
             ColorMode::Grayscale1Bit => StorageMode::PixelsPerByte(8),
             ColorMode::Grayscale2Bit => StorageMode::PixelsPerByte(4),
             ColorMode::Grayscale4Bit => StorageMode::PixelsPerByte(2),
+            ColorMode::Indexed1Bit => StorageMode::PixelsPerByte(8),
+            ColorMode::Indexed2Bit => StorageMode::PixelsPerByte(4),
+            ColorMode::Indexed4Bit => StorageMode::PixelsPerByte(2),
+
+            ColorMode::RGB444 => StorageMode::PixelGroup {
+                pixels: 2,
+                bytes: 3,
+            },
+        }
+    }
+}
+
+impl StorageMode {
+    /// Gives the buffer required length to host an image of the given dimensions.
+    ///
+    /// The packing is only used by the [`StorageMode::PixelsPerByte`] and
+    /// [`StorageMode::PixelGroup`] modes, where a row may end in the middle of a byte or group.
+    pub const fn buffer_length(&self, width: u16, height: u16, packing: SubpixelPacking) -> usize {
+        let width = width as usize;
+        let height = height as usize;
+
+        let (pixels, bytes) = match self {
+            StorageMode::BytesPerPixel(bytes) => return *bytes * width * height,
+            StorageMode::PixelsPerByte(pixels) => (*pixels, 1),
+            StorageMode::PixelGroup { pixels, bytes } => (*pixels, *bytes),
+        };
+
+        match packing {
+            SubpixelPacking::RowPadded => width.div_ceil(pixels) * bytes * height,
+            SubpixelPacking::Continuous => (width * height).div_ceil(pixels) * bytes,
+        }
+    }
+
+    /// Gives the number of pixels and the number of bytes of the smallest group of pixels
+    /// stored on whole bytes.
+    pub const fn group(&self) -> (usize, usize) {
+        match self {
+            StorageMode::BytesPerPixel(bytes) => (1, *bytes),
+            StorageMode::PixelsPerByte(pixels) => (*pixels, 1),
+            StorageMode::PixelGroup { pixels, bytes } => (*pixels, *bytes),
         }
     }
+
+    /// Gives the number of bits used by one pixel.
+    pub const fn bits_per_pixel(&self) -> u32 {
+        let (pixels, bytes) = self.group();
+
+        (8 * bytes / pixels) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorMode, StorageMode, SubpixelPacking};
+
+    #[test]
+    fn test_buffer_length() {
+        let padded = SubpixelPacking::RowPadded;
+        let continuous = SubpixelPacking::Continuous;
+
+        assert_eq!(ColorMode::RGB.byte_size().buffer_length(10, 3, padded), 90);
+        assert_eq!(
+            ColorMode::BGR565BE
+                .byte_size()
+                .buffer_length(10, 3, continuous),
+            60
+        );
+
+        assert_eq!(
+            ColorMode::Grayscale1Bit
+                .byte_size()
+                .buffer_length(16, 2, padded),
+            4
+        );
+        assert_eq!(
+            ColorMode::Grayscale1Bit
+                .byte_size()
+                .buffer_length(10, 3, padded),
+            6
+        );
+        assert_eq!(
+            ColorMode::Grayscale1Bit
+                .byte_size()
+                .buffer_length(10, 3, continuous),
+            4
+        );
+
+        assert_eq!(
+            StorageMode::PixelsPerByte(4).buffer_length(5, 5, padded),
+            10
+        );
+        assert_eq!(
+            StorageMode::PixelsPerByte(4).buffer_length(5, 5, continuous),
+            7
+        );
+        assert_eq!(
+            StorageMode::PixelsPerByte(2).buffer_length(3, 1, continuous),
+            2
+        );
+    }
+
+    #[test]
+    fn test_group() {
+        assert_eq!(ColorMode::RGB666.byte_size().group(), (1, 3));
+        assert_eq!(ColorMode::Grayscale2Bit.byte_size().group(), (4, 1));
+        assert_eq!(ColorMode::RGB444.byte_size().group(), (2, 3));
+
+        assert_eq!(ColorMode::XRGB8888.byte_size().bits_per_pixel(), 32);
+        assert_eq!(ColorMode::Indexed4Bit.byte_size().bits_per_pixel(), 4);
+        assert_eq!(ColorMode::RGB444.byte_size().bits_per_pixel(), 12);
+    }
+
+    #[test]
+    fn test_group_buffer_length() {
+        let padded = SubpixelPacking::RowPadded;
+        let continuous = SubpixelPacking::Continuous;
+        let rgb444 = ColorMode::RGB444.byte_size();
+
+        assert_eq!(rgb444.buffer_length(4, 3, padded), 18);
+        assert_eq!(rgb444.buffer_length(5, 3, padded), 27);
+        assert_eq!(rgb444.buffer_length(5, 3, continuous), 24);
+        assert_eq!(rgb444.buffer_length(5, 2, continuous), 15);
+
+        assert_eq!(
+            ColorMode::XRGB8888.byte_size().buffer_length(5, 3, padded),
+            60
+        );
+        assert_eq!(
+            ColorMode::RGB666.byte_size().buffer_length(5, 3, padded),
+            45
+        );
+        assert_eq!(
+            ColorMode::RGB332.byte_size().buffer_length(5, 3, padded),
+            15
+        );
+    }
 }
@@ -8,15 +8,55 @@ pub trait Component {
 
     fn max_component(self, other1: Self, other2: Self) -> Self;
 
+    /// Gives the weighted mean of the three components, rounded to the nearest value.
+    ///
+    /// The components of this crate give zero when all the weights are null.
+    fn weighted_mean(self, other1: Self, other2: Self, weights: [u32; 3]) -> Self
+    where
+        Self: Sized;
+
+    /// Gives the 8 most significant bits of the component, whatever the byte order of the host.
+    ///
+    /// Unlike [`Component::to_u8`], the result is truncated.
     fn most_significant_byte(self) -> u8;
+
+    /// Scales the component from its full range to an integer of `bits` bits, rounded to the
+    /// nearest value.
+    ///
+    /// The maximal value of the component always gives `2^bits - 1`, so narrower components are
+    /// expanded to the full target range. `bits` must be between 1 and 32. By default, only the
+    /// [`Component::most_significant_byte`] is scaled.
+    fn scale(self, bits: u32) -> u32
+    where
+        Self: Sized,
+    {
+        self.most_significant_byte().scale(bits)
+    }
+
+    /// Scales the component to the full range of an `u8`.
+    fn to_u8(self) -> u8
+    where
+        Self: Sized,
+    {
+        self.scale(8) as u8
+    }
+
+    /// Scales the component to the full range of an `u16`.
+    ///
+    /// Narrower components are expanded (`0xFF` gives `0xFFFF`) while wider components are
+    /// rounded to their 16 most significant bits.
+    fn to_u16(self) -> u16
+    where
+        Self: Sized,
+    {
+        self.scale(16) as u16
+    }
 }
 
 fn copy_buffer<const LENGTH: usize>(from: [u8; LENGTH], to: &mut [u8]) -> CoreResult<()> {
     CoreError::check_length(to, LENGTH)?;
 
-    for i in 0..LENGTH {
-        to[i] = from[i];
-    }
+    to.copy_from_slice(&from);
 
     Ok(())
 }
@@ -38,8 +78,38 @@ macro_rules! impl_component {
                 self.max(other1.max(other2))
             }
 
+            fn weighted_mean(self, other1: Self, other2: Self, weights: [u32; 3]) -> Self {
+                let total = weights.iter().map(|weight| *weight as u128).sum::<u128>();
+
+                if total == 0 {
+                    return 0;
+                }
+
+                // Splitting each component around the total keeps the products in range for
+                // the widest components
+                let mut quotient = 0u128;
+                let mut remainder = 0u128;
+
+                for (component, weight) in [self, other1, other2].into_iter().zip(weights) {
+                    quotient += component as u128 / total * weight as u128;
+                    remainder += component as u128 % total * weight as u128;
+                }
+
+                (quotient + (remainder + total / 2) / total) as Self
+            }
+
             fn most_significant_byte(self) -> u8 {
-                self.to_be() as u8
+                (self >> (<$t>::BITS - 8)) as u8
+            }
+
+            fn scale(self, bits: u32) -> u32 {
+                // Keeping 64 bits at most lets the product fit in an u128
+                let shift = <$t>::BITS.saturating_sub(64);
+                let value = (self >> shift) as u128;
+                let source_max = u128::MAX >> (128 - (<$t>::BITS - shift));
+                let target_max = (1u128 << bits) - 1;
+
+                ((value * target_max + source_max / 2) / source_max) as u32
             }
         }
     };
@@ -51,9 +121,74 @@ impl_component!(u32, 4);
 impl_component!(u64, 8);
 impl_component!(u128, 16);
 
+/// Implements [`Component`] for a floating point type ranging from 0 to 1.
+///
+/// The values out of range are clamped and NaN is taken as 0. The bytes of a converted component
+/// are the IEEE 754 representation of the value as is.
+macro_rules! impl_float_component {
+    ($t:ty, $length:literal) => {
+        impl Component for $t {
+            const LENGTH: usize = $length;
+
+            fn convert_le(self, buf: &mut [u8]) -> CoreResult<()> {
+                copy_buffer(self.to_le_bytes(), buf)
+            }
+
+            fn convert_be(self, buf: &mut [u8]) -> CoreResult<()> {
+                copy_buffer(self.to_be_bytes(), buf)
+            }
+
+            fn max_component(self, other1: Self, other2: Self) -> Self {
+                normalize(self as f64)
+                    .max(normalize(other1 as f64))
+                    .max(normalize(other2 as f64)) as Self
+            }
+
+            fn weighted_mean(self, other1: Self, other2: Self, weights: [u32; 3]) -> Self {
+                let total = weights.iter().map(|weight| *weight as f64).sum::<f64>();
+
+                if total == 0.0 {
+                    return 0.0;
+                }
+
+                let sum = [self, other1, other2]
+                    .into_iter()
+                    .zip(weights)
+                    .map(|(component, weight)| normalize(component as f64) * weight as f64)
+                    .sum::<f64>();
+
+                (sum / total) as Self
+            }
+
+            fn most_significant_byte(self) -> u8 {
+                (normalize(self as f64) * 256.0).min(255.0) as u8
+            }
+
+            fn scale(self, bits: u32) -> u32 {
+                let target_max = ((1u64 << bits) - 1) as f64;
+
+                (normalize(self as f64) * target_max).round() as u32
+            }
+        }
+    };
+}
+
+/// Clamps a floating point component between 0 and 1, NaN giving 0.
+fn normalize(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+impl_float_component!(f32, 4);
+impl_float_component!(f64, 8);
+
 #[cfg(test)]
 mod tests {
     use super::Component;
+    use crate::CoreResult;
 
     #[test]
     fn test_convert_le() {
@@ -122,4 +257,170 @@ mod tests {
             0x00112233445566778899AABBCCDDEEFFu128.most_significant_byte()
         );
     }
+
+    #[test]
+    fn test_weighted_mean() {
+        assert_eq!(85, 0xFFu8.weighted_mean(0, 0, [1, 1, 1]));
+        assert_eq!(170, 0xFFu8.weighted_mean(0xFF, 0, [1, 1, 1]));
+        assert_eq!(64, 0xFFu8.weighted_mean(0, 0, [1, 2, 1]));
+        assert_eq!(0, 0xFFu8.weighted_mean(0xFF, 0xFF, [0, 0, 0]));
+        assert_eq!(
+            0xFFFF,
+            0xFFFFu16.weighted_mean(0xFFFF, 0xFFFF, [299, 587, 114])
+        );
+        assert_eq!(
+            u128::MAX,
+            u128::MAX.weighted_mean(u128::MAX, u128::MAX, [u32::MAX, u32::MAX, u32::MAX])
+        );
+        assert_eq!(u128::MAX / 3, u128::MAX.weighted_mean(0, 0, [1, 1, 1]));
+    }
+
+    #[test]
+    fn test_most_significant_byte_order() {
+        assert_eq!(0xA5, 0xA5u8.most_significant_byte());
+        assert_eq!(0xA5, 0xA512u16.most_significant_byte());
+        assert_eq!(0x12, 0x12A5u16.most_significant_byte());
+        assert_eq!(0xA5, 0xA5123456u32.most_significant_byte());
+        assert_eq!(0x34, 0x3456A512u32.most_significant_byte());
+        assert_eq!(0xA5, 0xA500000000000012u64.most_significant_byte());
+        assert_eq!(0xA5, (0xA5u128 << 120 | 0x12).most_significant_byte());
+        assert_eq!(0xFF, u128::MAX.most_significant_byte());
+    }
+
+    #[test]
+    fn test_scale() {
+        // Narrowing
+        assert_eq!(0, 0x00u8.scale(5));
+        assert_eq!(31, 0xFFu8.scale(5));
+        assert_eq!(16, 0x80u8.scale(5));
+        assert_eq!(0x12, 0x1234u16.scale(8));
+        assert_eq!(0x13, 0x12FFu16.scale(8));
+        assert_eq!(63, 0xFFFFu16.scale(6));
+        assert_eq!(0x3FF, 0xFFFFFFFFu32.scale(10));
+        assert_eq!(0x200, 0x80000000u32.scale(10));
+        assert_eq!(0xFF, u64::MAX.scale(8));
+        assert_eq!(1, (u128::MAX / 2 + 1).scale(1));
+        assert_eq!(0, (u128::MAX / 2 - (1 << 64)).scale(1));
+        assert_eq!(u32::MAX, u128::MAX.scale(32));
+
+        // Widening
+        assert_eq!(0xFFFF, 0xFFu8.scale(16));
+        assert_eq!(0x8080, 0x80u8.scale(16));
+        assert_eq!(0xFFFFFFFF, 0xFFFFu16.scale(32));
+        assert_eq!(0x12341234, 0x1234u16.scale(32));
+        assert_eq!(1023, 0xFFu8.scale(10));
+    }
+
+    #[test]
+    fn test_to_u8() {
+        assert_eq!(0xA5, 0xA5u8.to_u8());
+        assert_eq!(0x12, 0x1234u16.to_u8());
+        assert_eq!(0x13, 0x12FFu16.to_u8());
+        assert_eq!(0xFF, 0xFFF0u16.to_u8());
+        assert_eq!(0x12, 0x12345678u32.to_u8());
+        assert_eq!(0x00, 0x0080000000000000u64.to_u8());
+        assert_eq!(0x01, 0x0081000000000000u64.to_u8());
+        assert_eq!(0xFF, u128::MAX.to_u8());
+    }
+
+    #[test]
+    fn test_to_u16() {
+        assert_eq!(0x0000, 0x00u8.to_u16());
+        assert_eq!(0xFFFF, 0xFFu8.to_u16());
+        assert_eq!(0x8080, 0x80u8.to_u16());
+        assert_eq!(0x1234, 0x1234u16.to_u16());
+        assert_eq!(0x1234, 0x12345678u32.to_u16());
+        assert_eq!(0x1235, 0x1234FFFFu32.to_u16());
+        assert_eq!(0x0011, 0x0011223344556677u64.to_u16());
+        assert_eq!(0xFFFF, u128::MAX.to_u16());
+    }
+
+    #[test]
+    fn test_float_scale() {
+        assert_eq!(0xFF, 1.0f32.to_u8());
+        assert_eq!(0x80, 0.5f32.to_u8());
+        assert_eq!(0x00, 0.0f64.to_u8());
+        assert_eq!(0xFFFF, 1.0f64.to_u16());
+        assert_eq!(0x8000, 0.5f64.to_u16());
+        assert_eq!(31, 1.0f32.scale(5));
+        assert_eq!(u32::MAX, 1.0f64.scale(32));
+
+        // Every 8 bits value survives a round trip
+        for value in 0..=0xFFu8 {
+            assert_eq!(value, (value as f32 / 255.0).to_u8());
+            assert_eq!(value, (value as f64 / 255.0).to_u8());
+        }
+    }
+
+    #[test]
+    fn test_float_out_of_range() {
+        assert_eq!(0xFF, 1.5f32.to_u8());
+        assert_eq!(0x00, (-0.5f32).to_u8());
+        assert_eq!(0xFF, f64::INFINITY.to_u8());
+        assert_eq!(0x00, f64::NEG_INFINITY.to_u8());
+        assert_eq!(0x00, f32::NAN.to_u8());
+        assert_eq!(0x00, f32::NAN.most_significant_byte());
+        assert_eq!(0xFF, 2.0f32.most_significant_byte());
+
+        assert_eq!(1.0, 2.0f32.max_component(0.5, f32::NAN));
+        assert_eq!(0.0, f64::NAN.max_component(-1.0, f64::NAN));
+        assert_eq!(0.5, 2.0f64.weighted_mean(0.0, f64::NAN, [1, 1, 0]));
+    }
+
+    #[test]
+    fn test_float_components() {
+        assert_eq!(0.5, 1.0f32.weighted_mean(0.0, 0.5, [1, 1, 2]));
+        assert_eq!(0.0, 1.0f64.weighted_mean(1.0, 1.0, [0, 0, 0]));
+        assert_eq!(0.75, 0.25f64.max_component(0.75, 0.5));
+
+        // Truncated, as the integer components
+        assert_eq!(0x7F, 0.499f32.most_significant_byte());
+        assert_eq!(0x80, 0.5f32.most_significant_byte());
+        assert_eq!(0xFF, 1.0f64.most_significant_byte());
+
+        let mut buf = [0u8; 4];
+
+        1.0f32.convert_be(&mut buf).unwrap();
+        assert_eq!(buf, [0x3F, 0x80, 0x00, 0x00]);
+    }
+
+    /// Component implementing only the required methods.
+    #[derive(Debug, PartialEq)]
+    struct Level(u16);
+
+    impl Component for Level {
+        const LENGTH: usize = 2;
+
+        fn convert_le(self, buf: &mut [u8]) -> CoreResult<()> {
+            self.0.convert_le(buf)
+        }
+
+        fn convert_be(self, buf: &mut [u8]) -> CoreResult<()> {
+            self.0.convert_be(buf)
+        }
+
+        fn max_component(self, other1: Self, other2: Self) -> Self {
+            Level(self.0.max_component(other1.0, other2.0))
+        }
+
+        fn weighted_mean(self, other1: Self, other2: Self, weights: [u32; 3]) -> Self {
+            Level(self.0.weighted_mean(other1.0, other2.0, weights))
+        }
+
+        fn most_significant_byte(self) -> u8 {
+            self.0.most_significant_byte()
+        }
+    }
+
+    #[test]
+    fn test_default_methods() {
+        assert_eq!(Level(0x12FF).scale(4), 0x1);
+        assert_eq!(Level(0xFFFF).scale(10), 0x3FF);
+        assert_eq!(Level(0xA5FF).to_u8(), 0xA5);
+        assert_eq!(Level(0xA5FF).to_u16(), 0xA5A5);
+        assert_eq!(
+            Level(0x10).weighted_mean(Level(0x30), Level(0x20), [1, 1, 1]),
+            Level(0x20)
+        );
+    }
 }
@@ -0,0 +1,318 @@
+use super::{ColorMode, FrameBuffer, Palette, PixelFormat, SubpixelPacking, TargetLayout};
+use crate::{CoreError, CoreResult};
+
+/// Decodes the bytes of a format back to RGB colors, the reverse of the
+/// [`Buffer`](super::Buffer) methods.
+///
+/// The pixels are decoded by [`PixelFormat::decode_pixel`]. For the color modes, the levels are
+/// expanded to the full range of an `u8`, so encoding the decoded colors again gives back the same
+/// bytes. The indexed color modes need the palette used to encode them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoder<F = ColorMode> {
+    format: F,
+    packing: SubpixelPacking,
+    palette: Option<Palette>,
+}
+
+impl<F: PixelFormat> Decoder<F> {
+    pub const fn new(format: F) -> Decoder<F> {
+        Decoder {
+            format,
+            packing: SubpixelPacking::RowPadded,
+            palette: None,
+        }
+    }
+
+    pub fn with_packing(mut self, packing: SubpixelPacking) -> Decoder<F> {
+        self.packing = packing;
+
+        self
+    }
+
+    pub fn with_palette(mut self, palette: Palette) -> Decoder<F> {
+        self.palette = Some(palette);
+
+        self
+    }
+
+    pub const fn format(&self) -> &F {
+        &self.format
+    }
+
+    pub const fn packing(&self) -> SubpixelPacking {
+        self.packing
+    }
+
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    /// Decodes an image of the given dimensions, the data holding exactly its rows.
+    pub fn decode(&self, data: &[u8], width: u16, height: u16) -> CoreResult<FrameBuffer<[u8; 3]>> {
+        CoreError::check_storage(self.format.storage())?;
+
+        let (row_length, rows, _) = self.rows(width, height);
+
+        CoreError::check_length(data, row_length * rows)?;
+
+        self.decode_strided(data, TargetLayout::new(row_length), width, height)
+    }
+
+    /// Decodes an image whose rows are placed according to the layout, as filled by
+    /// [`Buffer::fill_strided`](super::Buffer::fill_strided).
+    pub fn decode_strided(
+        &self,
+        data: &[u8],
+        layout: TargetLayout,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<FrameBuffer<[u8; 3]>> {
+        let storage = self.format.storage();
+
+        CoreError::check_storage(storage)?;
+
+        let (row_length, rows, row_pixels) = self.rows(width, height);
+        let (group_pixels, group_bytes) = storage.group();
+        let bits = storage.bits_per_pixel();
+        let mask = u64::MAX >> (64 - bits);
+        let slack = 8 * group_bytes as u32 - bits * group_pixels as u32;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+
+        for row in layout.rows(data, row_length, rows)? {
+            // The pixels of a group are stored from the most significant bits
+            let codes = row
+                .chunks_exact(group_bytes)
+                .flat_map(|group| {
+                    let value = group
+                        .iter()
+                        .fold(0u128, |value, byte| value << 8 | *byte as u128);
+
+                    (0..group_pixels)
+                        .rev()
+                        .map(move |pixel| (value >> (bits * pixel as u32 + slack)) as u64 & mask)
+                })
+                .take(row_pixels);
+
+            for code in codes {
+                pixels.push(self.format.decode_pixel(code, self.palette.as_ref())?);
+            }
+        }
+
+        FrameBuffer::from_pixels(width, height, pixels)
+    }
+
+    /// Gives the length in bytes and the number of rows of an image, and the number of pixels of
+    /// a row. When the pixels smaller than a byte are packed continuously, the whole image is one
+    /// single row.
+    fn rows(&self, width: u16, height: u16) -> (usize, usize, usize) {
+        let storage = self.format.storage();
+
+        if self.packing == SubpixelPacking::Continuous && storage.group().0 > 1 {
+            (
+                storage.buffer_length(width, height, self.packing),
+                1,
+                width as usize * height as usize,
+            )
+        } else {
+            (
+                storage.buffer_length(width, 1, SubpixelPacking::RowPadded),
+                height as usize,
+                width as usize,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::Decoder;
+    use crate::{
+        Buffer, ColorMode, CoreError, FillOptions, IntoPixelIter, Palette, PixelSlice,
+        SubpixelPacking, TargetLayout,
+    };
+
+    const MODES: [ColorMode; 21] = [
+        ColorMode::RGB,
+        ColorMode::BGR,
+        ColorMode::XRGB8888,
+        ColorMode::RGBA8888,
+        ColorMode::RGB666,
+        ColorMode::RGB444,
+        ColorMode::RGB332,
+        ColorMode::RGB565LE,
+        ColorMode::RGB565BE,
+        ColorMode::BGR565LE,
+        ColorMode::BGR565BE,
+        ColorMode::Grayscale1Bit,
+        ColorMode::Grayscale2Bit,
+        ColorMode::Grayscale4Bit,
+        ColorMode::Grayscale8Bit,
+        ColorMode::Grayscale16BitLE,
+        ColorMode::Grayscale16BitBE,
+        ColorMode::Indexed1Bit,
+        ColorMode::Indexed2Bit,
+        ColorMode::Indexed4Bit,
+        ColorMode::Indexed8Bit,
+    ];
+
+    fn decode(mode: ColorMode, data: &[u8], width: u16, height: u16) -> Vec<[u8; 3]> {
+        Decoder::new(mode)
+            .decode(data, width, height)
+            .unwrap()
+            .into_pixels()
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode(ColorMode::BGR565BE, &[0xF8, 0x00, 0x07, 0xE0], 2, 1),
+            [[0x00, 0x00, 0xFF], [0x00, 0xFF, 0x00]]
+        );
+        assert_eq!(
+            decode(ColorMode::RGB565LE, &[0x00, 0xF8, 0x10, 0x84], 2, 1),
+            [[0xFF, 0x00, 0x00], [0x84, 0x82, 0x84]]
+        );
+        assert_eq!(
+            decode(ColorMode::XRGB8888, &[0xFF, 0x12, 0x34, 0x56], 1, 1),
+            [[0x12, 0x34, 0x56]]
+        );
+        assert_eq!(
+            decode(ColorMode::RGBA8888, &[0x12, 0x34, 0x56, 0xFF], 1, 1),
+            [[0x12, 0x34, 0x56]]
+        );
+        assert_eq!(
+            decode(ColorMode::RGB666, &[0xFC, 0x80, 0x04], 1, 1),
+            [[0xFF, 0x82, 0x04]]
+        );
+        assert_eq!(
+            decode(ColorMode::RGB332, &[0b111_010_01], 1, 1),
+            [[0xFF, 0x49, 0x55]]
+        );
+        assert_eq!(
+            decode(ColorMode::RGB444, &[0xF0, 0x00, 0xF0], 2, 1),
+            [[0xFF, 0x00, 0x00], [0x00, 0xFF, 0x00]]
+        );
+        assert_eq!(
+            decode(ColorMode::Grayscale16BitLE, &[0x00, 0x80], 1, 1),
+            [[0x80; 3]]
+        );
+
+        // The last byte of each row is padded
+        assert_eq!(
+            decode(
+                ColorMode::Grayscale2Bit,
+                &[0b00_01_10_11, 0b10_00_00_00],
+                5,
+                1
+            ),
+            [[0x00; 3], [0x55; 3], [0xAA; 3], [0xFF; 3], [0xAA; 3]]
+        );
+        assert_eq!(
+            decode(ColorMode::Grayscale1Bit, &[0b1010_0000, 0b0100_0000], 3, 2),
+            [[0xFF; 3], [0x00; 3], [0xFF; 3], [0x00; 3], [0xFF; 3], [0x00; 3]]
+        );
+        assert_eq!(
+            Decoder::new(ColorMode::Grayscale1Bit)
+                .with_packing(SubpixelPacking::Continuous)
+                .decode(&[0b1010_1000], 3, 2)
+                .unwrap()
+                .into_pixels(),
+            [[0xFF; 3], [0x00; 3], [0xFF; 3], [0x00; 3], [0xFF; 3], [0x00; 3]]
+        );
+    }
+
+    #[test]
+    fn test_decode_indexed() {
+        let palette = Palette::new(&[[0x10, 0x20, 0x30], [0xFF, 0x80, 0x00]]).unwrap();
+        let decoder = Decoder::new(ColorMode::Indexed2Bit).with_palette(palette);
+
+        // The index 3 has no color
+        assert_eq!(
+            decoder
+                .decode(&[0b01_00_11_01], 4, 1)
+                .unwrap()
+                .into_pixels(),
+            [
+                [0xFF, 0x80, 0x00],
+                [0x10, 0x20, 0x30],
+                [0x00, 0x00, 0x00],
+                [0xFF, 0x80, 0x00]
+            ]
+        );
+        assert_eq!(
+            Decoder::new(ColorMode::Indexed2Bit).decode(&[0], 4, 1),
+            Err(CoreError::MissingPalette)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pixels = (0..35u32)
+            .map(|i| [(i * 37) as u8, (i * 91 + 13) as u8, (i * 53 + 200) as u8])
+            .collect::<Vec<_>>();
+        let palette = Palette::median_cut(PixelSlice::new(&pixels, 7), 7, 5, 2).unwrap();
+
+        for packing in [SubpixelPacking::RowPadded, SubpixelPacking::Continuous] {
+            for mode in MODES {
+                let source = PixelSlice::new(&pixels, 7)
+                    .with_options(FillOptions::new().with_packing(packing))
+                    .with_palette(palette.clone());
+                let decoder = Decoder::new(mode)
+                    .with_packing(packing)
+                    .with_palette(palette.clone());
+                let length = mode.byte_size().buffer_length(5, 4, packing);
+                let mut encoded = vec![0u8; length];
+                let mut encoded_again = vec![0u8; length];
+
+                source.clone().fill(mode, &mut encoded, 1, 1, 5, 4).unwrap();
+
+                let decoded = decoder.decode(&encoded, 5, 4).unwrap();
+
+                (&decoded)
+                    .with_options(source.fill_options())
+                    .with_palette(palette.clone())
+                    .fill(mode, &mut encoded_again, 0, 0, 5, 4)
+                    .unwrap();
+
+                assert_eq!(encoded_again, encoded, "{mode:?} {packing:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_strided() {
+        let layout = TargetLayout::new(5).with_offset(2);
+        let data = [
+            0xAA, 0xAA, 0x00, 0x00, 0xF8, 0x00, 0xAA, 0x07, 0xE0, 0x00, 0x1F,
+        ];
+        let decoder = Decoder::new(ColorMode::RGB565BE);
+
+        assert_eq!(
+            decoder
+                .decode_strided(&data, layout, 2, 2)
+                .unwrap()
+                .into_pixels(),
+            [
+                [0x00, 0x00, 0x00],
+                [0xFF, 0x00, 0x00],
+                [0x00, 0xFF, 0x00],
+                [0x00, 0x00, 0xFF]
+            ]
+        );
+        assert_eq!(
+            decoder.decode_strided(&data[..10], layout, 2, 2),
+            Err(CoreError::BufferSizeError {
+                given: 10,
+                expected: 11
+            })
+        );
+        assert_eq!(
+            decoder.decode(&data, 2, 2),
+            Err(CoreError::BufferSizeError {
+                given: 11,
+                expected: 8
+            })
+        );
+    }
+}
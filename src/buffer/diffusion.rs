@@ -0,0 +1,204 @@
+/// Kernel spreading the quantization error of a pixel to its unprocessed neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffusionKernel {
+    /// Spreads the whole error on 4 pixels of the current and the next line.
+    FloydSteinberg,
+    /// Spreads three quarters of the error on 6 pixels of the three next lines, keeping more
+    /// contrast than the other kernels.
+    Atkinson,
+    /// Spreads the whole error on 3 pixels of the current and the next line. Lighter version of
+    /// the Sierra kernel.
+    SierraLite,
+}
+
+/// Largest horizontal reach of the kernels.
+const MARGIN: usize = 2;
+
+/// Number of lines covered by the kernels, including the current one.
+const LINES: usize = 3;
+
+/// Error diffusion state of an image processed line by line.
+///
+/// The values are scaled on 16 bits and the errors are kept in 1/divisor units to avoid
+/// accumulating rounding errors.
+pub(crate) struct Diffuser<const CHANNELS: usize> {
+    kernel: DiffusionKernel,
+    serpentine: bool,
+    max: [i32; CHANNELS],
+    errors: [Vec<[i32; CHANNELS]>; LINES],
+    line: usize,
+}
+
+impl DiffusionKernel {
+    /// Gives the neighbours receiving the error as `(dx, dy, weight)`, and the divisor of the
+    /// weights. The offsets are given for a left to right scan.
+    const fn taps(&self) -> (&'static [(isize, usize, i32)], i32) {
+        match self {
+            DiffusionKernel::FloydSteinberg => (&[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)], 16),
+            DiffusionKernel::Atkinson => (
+                &[
+                    (1, 0, 1),
+                    (2, 0, 1),
+                    (-1, 1, 1),
+                    (0, 1, 1),
+                    (1, 1, 1),
+                    (0, 2, 1),
+                ],
+                8,
+            ),
+            DiffusionKernel::SierraLite => (&[(1, 0, 2), (-1, 1, 1), (0, 1, 1)], 4),
+        }
+    }
+}
+
+impl<const CHANNELS: usize> Diffuser<CHANNELS> {
+    /// Creates the state of an image whose lines have `width` pixels at most, each channel being
+    /// reduced to the given number of bits.
+    pub(crate) fn new(
+        kernel: DiffusionKernel,
+        serpentine: bool,
+        bits: [u32; CHANNELS],
+        width: usize,
+    ) -> Self {
+        Diffuser {
+            kernel,
+            serpentine,
+            max: bits.map(|bits| (1 << bits) - 1),
+            errors: std::array::from_fn(|_| vec![[0; CHANNELS]; width + 2 * MARGIN]),
+            line: 0,
+        }
+    }
+
+    /// Quantizes the next line of the image.
+    ///
+    /// Each value of `line` is a channel scaled on 16 bits and is replaced by its level. With a
+    /// serpentine scan, the odd lines are processed from right to left.
+    pub(crate) fn diffuse(&mut self, line: &mut [[i32; CHANNELS]]) {
+        let (taps, divisor) = self.kernel.taps();
+        let reverse = self.serpentine && self.line % 2 == 1;
+        let width = line.len();
+
+        for i in 0..width {
+            let x = if reverse { width - 1 - i } else { i };
+            let error = self.errors[0][x + MARGIN];
+
+            for channel in 0..CHANNELS {
+                let max = self.max[channel];
+                let value = (line[x][channel] + error[channel] / divisor).clamp(0, 0xFFFF);
+                let level = (value * max + 0x7FFF) / 0xFFFF;
+                let residual = value - level * 0xFFFF / max;
+
+                line[x][channel] = level;
+
+                for &(dx, dy, weight) in taps {
+                    let dx = if reverse { -dx } else { dx };
+                    let neighbour = (x + MARGIN).wrapping_add_signed(dx);
+
+                    self.errors[dy][neighbour][channel] += residual * weight;
+                }
+            }
+        }
+
+        self.errors.rotate_left(1);
+        self.errors[LINES - 1].fill([0; CHANNELS]);
+        self.line += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diffuser, DiffusionKernel};
+
+    const KERNELS: [DiffusionKernel; 3] = [
+        DiffusionKernel::FloydSteinberg,
+        DiffusionKernel::Atkinson,
+        DiffusionKernel::SierraLite,
+    ];
+
+    fn diffuse<const WIDTH: usize>(
+        kernel: DiffusionKernel,
+        serpentine: bool,
+        bits: u32,
+        lines: &[[i32; WIDTH]],
+    ) -> Vec<Vec<i32>> {
+        let mut diffuser = Diffuser::new(kernel, serpentine, [bits], WIDTH);
+
+        lines
+            .iter()
+            .map(|values| {
+                let mut line = values.iter().map(|value| [*value]).collect::<Vec<_>>();
+
+                diffuser.diffuse(&mut line);
+
+                line.into_iter().map(|[level]| level).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_weights() {
+        assert_eq!(DiffusionKernel::FloydSteinberg.taps().1, 16);
+        assert_eq!(DiffusionKernel::Atkinson.taps().1, 8);
+        assert_eq!(DiffusionKernel::SierraLite.taps().1, 4);
+
+        for kernel in [DiffusionKernel::FloydSteinberg, DiffusionKernel::SierraLite] {
+            let (taps, divisor) = kernel.taps();
+
+            assert_eq!(taps.iter().map(|tap| tap.2).sum::<i32>(), divisor);
+        }
+
+        let (taps, _) = DiffusionKernel::Atkinson.taps();
+
+        assert_eq!(taps.iter().map(|tap| tap.2).sum::<i32>(), 6);
+    }
+
+    #[test]
+    fn test_exact_values() {
+        // The representable values do not produce any error
+        for kernel in KERNELS {
+            let lines = diffuse(kernel, true, 2, &[[0, 0x5555, 0xAAAA, 0xFFFF]; 3]);
+
+            assert_eq!(lines, vec![vec![0, 1, 2, 3]; 3]);
+        }
+    }
+
+    #[test]
+    fn test_floyd_steinberg() {
+        let lines = diffuse(DiffusionKernel::FloydSteinberg, false, 1, &[[0x8080; 4]; 2]);
+
+        assert_eq!(lines[0], [1, 0, 1, 0]);
+        assert_eq!(lines[1], [0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_serpentine() {
+        let lines = diffuse(DiffusionKernel::SierraLite, true, 1, &[[0x4000; 4]; 2]);
+
+        // The second line is processed from right to left
+        assert_eq!(lines[0], [0, 0, 0, 0]);
+        assert_eq!(lines[1], [1, 0, 1, 0]);
+
+        let lines = diffuse(DiffusionKernel::SierraLite, false, 1, &[[0x4000; 4]; 2]);
+
+        assert_eq!(lines[0], [0, 0, 0, 0]);
+        assert_eq!(lines[1], [0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_mean_preserved() {
+        const WIDTH: usize = 32;
+
+        for kernel in [DiffusionKernel::FloydSteinberg, DiffusionKernel::SierraLite] {
+            for value in [0x1000, 0x4000, 0x8000, 0xC000] {
+                let lines = diffuse(kernel, true, 1, &[[value; WIDTH]; WIDTH]);
+                let ones = lines.concat().into_iter().sum::<i32>();
+                let expected = value * (WIDTH * WIDTH) as i32 / 0xFFFF;
+
+                assert!(
+                    (ones - expected).abs() <= WIDTH as i32,
+                    "{kernel:?} {value}"
+                );
+            }
+        }
+    }
+}
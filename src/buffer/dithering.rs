@@ -0,0 +1,118 @@
+use super::DiffusionKernel;
+
+/// Dithering applied when a source is reduced to a low-depth color mode.
+///
+/// The dithering is used by the RGB565/BGR565 and the 1, 2 and 4 bits grayscale modes. When
+/// enabled, it replaces the [`Quantizer`](super::Quantizer) of the [`FillOptions`](super::FillOptions).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dithering {
+    #[default]
+    None,
+    /// Ordered dithering using a 2x2 Bayer matrix.
+    Bayer2x2,
+    /// Ordered dithering using a 4x4 Bayer matrix.
+    Bayer4x4,
+    /// Ordered dithering using a 8x8 Bayer matrix.
+    Bayer8x8,
+    /// Error diffusion dithering.
+    ///
+    /// The error is only spread inside the updated area, so a partial update may not match the
+    /// same area of a full frame update. With a serpentine scan, every other line is processed
+    /// from right to left, which avoids the directional artifacts.
+    Diffusion {
+        kernel: DiffusionKernel,
+        serpentine: bool,
+    },
+}
+
+const BAYER_2X2: [[u8; 2]; 2] = [[0, 2], [3, 1]];
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+impl Dithering {
+    /// Gives the threshold of the pixel at the given absolute position, in 1/65536 of a
+    /// quantization step.
+    ///
+    /// The matrix is keyed on the position in the screen rather than in the updated area, so
+    /// two adjacent updates give the same pattern as a single one.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the pixels are not dithered by an ordered matrix.
+    pub const fn threshold(&self, x: u32, y: u32) -> Option<u16> {
+        let x = x as usize;
+        let y = y as usize;
+
+        let (value, size) = match self {
+            Dithering::None | Dithering::Diffusion { .. } => return None,
+            Dithering::Bayer2x2 => (BAYER_2X2[y % 2][x % 2], 2),
+            Dithering::Bayer4x4 => (BAYER_4X4[y % 4][x % 4], 4),
+            Dithering::Bayer8x8 => (BAYER_8X8[y % 8][x % 8], 8),
+        };
+
+        Some(((2 * value as u32 + 1) * 0x8000 / (size * size)) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiffusionKernel, Dithering, BAYER_2X2, BAYER_4X4, BAYER_8X8};
+
+    fn check_matrix<const SIZE: usize>(matrix: [[u8; SIZE]; SIZE]) {
+        let mut values = matrix.concat();
+
+        values.sort();
+
+        assert!(values.into_iter().eq(0..(SIZE * SIZE) as u8));
+    }
+
+    #[test]
+    fn test_matrices() {
+        check_matrix(BAYER_2X2);
+        check_matrix(BAYER_4X4);
+        check_matrix(BAYER_8X8);
+
+        // Each matrix is made of the smaller one
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(BAYER_4X4[y % 4][x % 4] / 4, BAYER_2X2[y % 2][x % 2]);
+                assert_eq!(BAYER_8X8[y][x] / 4, BAYER_4X4[y % 4][x % 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_threshold() {
+        assert_eq!(Dithering::None.threshold(0, 0), None);
+        assert_eq!(
+            Dithering::Diffusion {
+                kernel: DiffusionKernel::FloydSteinberg,
+                serpentine: true
+            }
+            .threshold(0, 0),
+            None
+        );
+
+        assert_eq!(Dithering::Bayer2x2.threshold(0, 0), Some(0x2000));
+        assert_eq!(Dithering::Bayer2x2.threshold(1, 0), Some(0xA000));
+        assert_eq!(Dithering::Bayer2x2.threshold(0, 1), Some(0xE000));
+        assert_eq!(Dithering::Bayer2x2.threshold(3, 3), Some(0x6000));
+
+        assert_eq!(Dithering::Bayer4x4.threshold(0, 0), Some(0x0800));
+        assert_eq!(Dithering::Bayer4x4.threshold(4, 3), Some(0xF800));
+
+        assert_eq!(Dithering::Bayer8x8.threshold(0, 0), Some(0x0200));
+        assert_eq!(Dithering::Bayer8x8.threshold(8, 7), Some(0xFE00));
+    }
+}
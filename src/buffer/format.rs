@@ -0,0 +1,722 @@
+use super::{
+    encode_mode, grayscale_level, next_full_row, Color, ColorMode, Component, Dithering,
+    FillOptions, FillTarget, Palette, PixelSource, StorageMode, SubpixelPacking,
+};
+use crate::{CoreError, CoreResult};
+
+/// Encoding of the pixels in the bytes of a target, such as the color modes of [`ColorMode`] or
+/// the format of a specific device.
+///
+/// Each pixel is encoded into a code of [`StorageMode::bits_per_pixel`] bits. The codes of a
+/// group of pixels, see [`StorageMode::group`], are stored from the most significant bits to the
+/// least significant ones and the bytes of a group in big endian, a group holding at most 16
+/// bytes and a code at most 64 bits. The other storages are rejected with
+/// [`CoreError::UnsupportedStorage`]. A format storing its
+/// pixels in little endian thus swaps the bytes of its codes.
+///
+/// [`PixelFormat::encode`] converts an area pixel by pixel. A format can override it with a faster
+/// conversion, as [`ColorMode`] does, or to spread the error of [`Dithering::Diffusion`], which
+/// the pixel by pixel conversion rejects with [`CoreError::UnsupportedDithering`].
+pub trait PixelFormat {
+    /// Gives how the pixels are stored in the target.
+    fn storage(&self) -> StorageMode;
+
+    /// Encodes a pixel located at the given absolute position into its code. The palette is the
+    /// one of the source, see [`IntoPixelIter::with_palette`](super::IntoPixelIter::with_palette).
+    fn encode_pixel<C: Color>(
+        &self,
+        pixel: C,
+        options: &FillOptions,
+        palette: Option<&Palette>,
+        position: (u32, u32),
+    ) -> CoreResult<u64>;
+
+    /// Decodes the code of a pixel back to a RGB color, the reverse of
+    /// [`PixelFormat::encode_pixel`].
+    fn decode_pixel(&self, code: u64, palette: Option<&Palette>) -> CoreResult<[u8; 3]>;
+
+    /// Fills the target with the pixels of an area of the source.
+    ///
+    /// The target is checked as in [`FillTarget::chunks`]. Each row begins on a new group, unless
+    /// the source uses the [`SubpixelPacking::Continuous`] packing with several pixels per group.
+    fn encode<S: PixelSource>(
+        &self,
+        source: S,
+        target: FillTarget,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        encode_pixels(self, source, target, x, y, width, height)
+    }
+}
+
+/// Fills the target with the codes of the pixels, packed in groups.
+fn encode_pixels<F: PixelFormat + ?Sized, S: PixelSource>(
+    format: &F,
+    source: S,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    let options = source.fill_options();
+    let palette = source.palette();
+    let storage = format.storage();
+
+    CoreError::check_storage(storage)?;
+
+    // The error of a pixel cannot be spread without knowing how the format quantizes it
+    if let dithering @ Dithering::Diffusion { .. } = options.dithering() {
+        return Err(CoreError::UnsupportedDithering { dithering });
+    }
+
+    let (group_pixels, group_bytes) = storage.group();
+    let bits = storage.bits_per_pixel();
+    let mask = u64::MAX >> (64 - bits);
+    let slack = 8 * group_bytes as u32 - bits * group_pixels as u32;
+
+    let continuous = options.packing() == SubpixelPacking::Continuous && group_pixels > 1;
+    let (row_length, rows) = if continuous {
+        (storage.buffer_length(width, height, options.packing()), 1)
+    } else {
+        (
+            storage.buffer_length(width, 1, SubpixelPacking::RowPadded),
+            height as usize,
+        )
+    };
+
+    let mut groups = target.chunks(row_length, rows, group_bytes)?;
+    let mut scanlines = source.into_scanlines(x, y, width, height);
+    let mut group = 0u128;
+    let mut pixels = 0;
+
+    // The missing pixels of the last group of a row are padded with zeros, as the bits left
+    // unused by a whole group
+    let mut flush = |group: &mut u128, pixels: &mut usize| {
+        if let Some(buf) = groups.next() {
+            let value = *group << (bits * (group_pixels - *pixels) as u32 + slack);
+
+            buf.copy_from_slice(&value.to_be_bytes()[16 - group_bytes..]);
+        }
+
+        *group = 0;
+        *pixels = 0;
+    };
+
+    for row_y in (y as u32..).take(height as usize) {
+        let Some(row) = next_full_row(&mut scanlines, width)? else {
+            break;
+        };
+
+        for (row_x, pixel) in (x as u32..).zip(row) {
+            let code = format.encode_pixel(pixel, &options, palette.as_ref(), (row_x, row_y))?;
+
+            group = group << bits | (code & mask) as u128;
+            pixels += 1;
+
+            if pixels == group_pixels {
+                flush(&mut group, &mut pixels);
+            }
+        }
+
+        if !continuous && pixels > 0 {
+            flush(&mut group, &mut pixels);
+        }
+    }
+
+    if pixels > 0 {
+        flush(&mut group, &mut pixels);
+    }
+
+    Ok(())
+}
+
+/// Gives the code of a pixel encoded in `length` bytes, read in big endian. The code of a
+/// [`ColorMode`] holds at most 4 bytes.
+fn read_code(length: usize, fill: impl FnOnce(&mut [u8]) -> CoreResult<()>) -> CoreResult<u32> {
+    let mut buf = [0u8; 4];
+
+    fill(&mut buf[..length])?;
+
+    Ok(buf[..length]
+        .iter()
+        .fold(0, |code, byte| code << 8 | *byte as u32))
+}
+
+/// Expands a level of `bits` bits to the full range of an `u8`, rounded to the nearest value.
+const fn expand(level: u32, bits: u32) -> u8 {
+    let maximum = (1 << bits) - 1;
+
+    ((level * 255 + maximum / 2) / maximum) as u8
+}
+
+const fn rgb565(value: u16) -> [u8; 3] {
+    let value = value as u32;
+
+    [
+        expand(value >> 11, 5),
+        expand(value >> 5 & 0x3F, 6),
+        expand(value & 0x1F, 5),
+    ]
+}
+
+const fn bgr565(value: u16) -> [u8; 3] {
+    let [blue, green, red] = rgb565(value);
+
+    [red, green, blue]
+}
+
+impl PixelFormat for ColorMode {
+    fn storage(&self) -> StorageMode {
+        self.byte_size()
+    }
+
+    fn encode_pixel<C: Color>(
+        &self,
+        pixel: C,
+        options: &FillOptions,
+        palette: Option<&Palette>,
+        (x, y): (u32, u32),
+    ) -> CoreResult<u64> {
+        let quantizer = options.quantizer_at(x, y);
+
+        let code = match self {
+            ColorMode::RGB => read_code(3, |buf| pixel.fill_rgb(buf)),
+            ColorMode::BGR => read_code(3, |buf| pixel.fill_bgr(buf)),
+            ColorMode::XRGB8888 => read_code(4, |buf| pixel.fill_xrgb8888(buf)),
+            ColorMode::RGBA8888 => read_code(4, |buf| pixel.fill_rgba8888(buf)),
+            ColorMode::RGB666 => read_code(3, |buf| pixel.fill_rgb666_with(quantizer, buf)),
+            ColorMode::RGB444 => Ok(pixel.into_rgb444_with(quantizer) as u32),
+            ColorMode::RGB332 => read_code(1, |buf| pixel.fill_rgb332_with(quantizer, buf)),
+            ColorMode::RGB565LE => read_code(2, |buf| pixel.fill_rgb565le_with(quantizer, buf)),
+            ColorMode::RGB565BE => read_code(2, |buf| pixel.fill_rgb565be_with(quantizer, buf)),
+            ColorMode::BGR565LE => read_code(2, |buf| pixel.fill_bgr565le_with(quantizer, buf)),
+            ColorMode::BGR565BE => read_code(2, |buf| pixel.fill_bgr565be_with(quantizer, buf)),
+            ColorMode::Grayscale1Bit => Ok(grayscale_level::<C, 1>(pixel, options, (x, y))),
+            ColorMode::Grayscale2Bit => Ok(grayscale_level::<C, 2>(pixel, options, (x, y))),
+            ColorMode::Grayscale4Bit => Ok(grayscale_level::<C, 4>(pixel, options, (x, y))),
+            ColorMode::Grayscale8Bit => read_code(1, |buf| {
+                pixel.fill_grayscale_8bit_with(options.luminance(), buf)
+            }),
+            ColorMode::Grayscale16BitLE => read_code(2, |buf| {
+                pixel.fill_grayscale_16bit_le_with(options.luminance(), buf)
+            }),
+            ColorMode::Grayscale16BitBE => read_code(2, |buf| {
+                pixel.fill_grayscale_16bit_be_with(options.luminance(), buf)
+            }),
+            ColorMode::Indexed1Bit
+            | ColorMode::Indexed2Bit
+            | ColorMode::Indexed4Bit
+            | ColorMode::Indexed8Bit => {
+                let palette = palette.ok_or(CoreError::MissingPalette)?;
+
+                CoreError::check_palette(palette.len(), 1 << self.byte_size().bits_per_pixel())?;
+
+                Ok(palette.nearest(pixel.components().map(Component::to_u8)) as u32)
+            }
+        }?;
+
+        Ok(code as u64)
+    }
+
+    /// Decodes a pixel, its levels being expanded to the full range of an `u8` so encoding the
+    /// color again gives back the same code. The indexes without color of a palette give black.
+    fn decode_pixel(&self, code: u64, palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+        // The codes of the color modes hold 32 bits at most
+        let code = code as u32;
+        let color = match self {
+            ColorMode::RGB | ColorMode::XRGB8888 => {
+                [code >> 16, code >> 8, code].map(|component| component as u8)
+            }
+            ColorMode::BGR => [code, code >> 8, code >> 16].map(|component| component as u8),
+            ColorMode::RGBA8888 => {
+                [code >> 24, code >> 16, code >> 8].map(|component| component as u8)
+            }
+            ColorMode::RGB666 => {
+                [code >> 18, code >> 10, code >> 2].map(|component| expand(component & 0x3F, 6))
+            }
+            ColorMode::RGB444 => {
+                [code >> 8, code >> 4, code].map(|component| expand(component & 0xF, 4))
+            }
+            ColorMode::RGB332 => [
+                expand(code >> 5 & 0x7, 3),
+                expand(code >> 2 & 0x7, 3),
+                expand(code & 0x3, 2),
+            ],
+            ColorMode::RGB565LE => rgb565((code as u16).swap_bytes()),
+            ColorMode::RGB565BE => rgb565(code as u16),
+            ColorMode::BGR565LE => bgr565((code as u16).swap_bytes()),
+            ColorMode::BGR565BE => bgr565(code as u16),
+            ColorMode::Grayscale1Bit
+            | ColorMode::Grayscale2Bit
+            | ColorMode::Grayscale4Bit
+            | ColorMode::Grayscale8Bit => [expand(code, self.byte_size().bits_per_pixel()); 3],
+            ColorMode::Grayscale16BitLE => [expand((code as u16).swap_bytes() as u32, 16); 3],
+            ColorMode::Grayscale16BitBE => [expand(code & 0xFFFF, 16); 3],
+            ColorMode::Indexed1Bit
+            | ColorMode::Indexed2Bit
+            | ColorMode::Indexed4Bit
+            | ColorMode::Indexed8Bit => palette
+                .ok_or(CoreError::MissingPalette)?
+                .colors()
+                .get(code as usize)
+                .copied()
+                .unwrap_or_default(),
+        };
+
+        Ok(color)
+    }
+
+    fn encode<S: PixelSource>(
+        &self,
+        source: S,
+        target: FillTarget,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        encode_mode(source, *self, target, x, y, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PixelFormat;
+    use crate::{
+        Buffer, Color, ColorMode, Component, CoreError, CoreResult, Decoder, DiffusionKernel,
+        Dithering, FillOptions, FillTarget, IntoPixelIter, Luminance, Palette, PixelSlice,
+        StorageMode, SubpixelPacking, TargetLayout,
+    };
+
+    const MODES: [ColorMode; 21] = [
+        ColorMode::RGB,
+        ColorMode::BGR,
+        ColorMode::XRGB8888,
+        ColorMode::RGBA8888,
+        ColorMode::RGB666,
+        ColorMode::RGB444,
+        ColorMode::RGB332,
+        ColorMode::RGB565LE,
+        ColorMode::RGB565BE,
+        ColorMode::BGR565LE,
+        ColorMode::BGR565BE,
+        ColorMode::Grayscale1Bit,
+        ColorMode::Grayscale2Bit,
+        ColorMode::Grayscale4Bit,
+        ColorMode::Grayscale8Bit,
+        ColorMode::Grayscale16BitLE,
+        ColorMode::Grayscale16BitBE,
+        ColorMode::Indexed1Bit,
+        ColorMode::Indexed2Bit,
+        ColorMode::Indexed4Bit,
+        ColorMode::Indexed8Bit,
+    ];
+
+    /// A color mode converted pixel by pixel, through the provided encoding.
+    struct PerPixel(ColorMode);
+
+    impl PixelFormat for PerPixel {
+        fn storage(&self) -> StorageMode {
+            self.0.storage()
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            pixel: C,
+            options: &FillOptions,
+            palette: Option<&Palette>,
+            position: (u32, u32),
+        ) -> CoreResult<u64> {
+            self.0.encode_pixel(pixel, options, palette, position)
+        }
+
+        fn decode_pixel(&self, code: u64, palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            self.0.decode_pixel(code, palette)
+        }
+    }
+
+    /// A 15 bits format in little endian, the most significant bit being always set.
+    struct Xrgb1555;
+
+    impl PixelFormat for Xrgb1555 {
+        fn storage(&self) -> StorageMode {
+            StorageMode::BytesPerPixel(2)
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            pixel: C,
+            _options: &FillOptions,
+            _palette: Option<&Palette>,
+            _position: (u32, u32),
+        ) -> CoreResult<u64> {
+            let [red, green, blue] = pixel
+                .components()
+                .map(|component| (component.to_u8() >> 3) as u16);
+
+            Ok((0x8000 | red << 10 | green << 5 | blue).swap_bytes() as u64)
+        }
+
+        fn decode_pixel(&self, code: u64, _palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            let code = (code as u16).swap_bytes();
+
+            Ok([code >> 10, code >> 5, code].map(|level| ((level & 0x1F) << 3) as u8))
+        }
+    }
+
+    fn pixels() -> Vec<[u8; 3]> {
+        (0..7 * 5)
+            .map(|i| [(i * 37 % 256) as u8, (i * 11) as u8, (255 - i * 5) as u8])
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_pixels() {
+        let pixels = pixels();
+        let palette = Palette::new(&[[0x20, 0x80, 0xC0], [0xFF, 0xFF, 0xFF]]).unwrap();
+        let options = [
+            FillOptions::new(),
+            FillOptions::new().with_dithering(Dithering::Bayer4x4),
+            FillOptions::new().with_luminance(Luminance::Rec709),
+            FillOptions::new().with_packing(SubpixelPacking::Continuous),
+            FillOptions::new()
+                .with_packing(SubpixelPacking::Continuous)
+                .with_dithering(Dithering::Bayer8x8),
+        ];
+
+        for options in options {
+            for mode in MODES {
+                let source = PixelSlice::new(&pixels, 7)
+                    .with_options(options)
+                    .with_palette(palette.clone());
+                let length = mode.byte_size().buffer_length(5, 3, options.packing());
+                let mut expected = vec![0u8; length];
+                let mut buffer = vec![0xAAu8; length];
+
+                source
+                    .clone()
+                    .fill(mode, &mut expected, 1, 2, 5, 3)
+                    .unwrap();
+                source
+                    .clone()
+                    .fill(PerPixel(mode), &mut buffer, 1, 2, 5, 3)
+                    .unwrap();
+                assert_eq!(buffer, expected, "{mode:?} {options:?}");
+
+                // The stride is not used by all the color modes with the continuous packing
+                if options.packing() == SubpixelPacking::Continuous {
+                    continue;
+                }
+
+                let layout = TargetLayout::new(length + 2).with_offset(1);
+                let mut expected = vec![0x55u8; 3 * length + 4];
+                let mut buffer = expected.clone();
+
+                source
+                    .clone()
+                    .fill_strided(mode, &mut expected, layout, 1, 2, 5, 3)
+                    .unwrap();
+                source
+                    .fill_strided(PerPixel(mode), &mut buffer, layout, 1, 2, 5, 3)
+                    .unwrap();
+                assert_eq!(buffer, expected, "{mode:?} {options:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let pixels = pixels();
+        let source = PixelSlice::new(&pixels, 7);
+        let mut buffer = [0u8; 2];
+
+        assert_eq!(
+            source.fill(PerPixel(ColorMode::Indexed2Bit), &mut buffer, 0, 0, 7, 1),
+            Err(CoreError::MissingPalette)
+        );
+
+        // The source ends before the area, which is not checked when encoding directly
+        assert_eq!(
+            PerPixel(ColorMode::Grayscale8Bit).encode(
+                source,
+                FillTarget::packed(&mut [0; 14]),
+                0,
+                4,
+                7,
+                2
+            ),
+            Err(CoreError::BufferSizeError {
+                given: 0,
+                expected: 7
+            })
+        );
+
+        let dithering = Dithering::Diffusion {
+            kernel: DiffusionKernel::FloydSteinberg,
+            serpentine: false,
+        };
+
+        assert_eq!(
+            source
+                .with_options(FillOptions::new().with_dithering(dithering))
+                .fill(PerPixel(ColorMode::RGB565BE), &mut [0; 14], 0, 0, 7, 1),
+            Err(CoreError::UnsupportedDithering { dithering })
+        );
+        assert_eq!(
+            source.fill(PerPixel(ColorMode::Grayscale4Bit), &mut buffer, 0, 0, 7, 1),
+            Err(CoreError::BufferSizeError {
+                given: 2,
+                expected: 4
+            })
+        );
+
+        let palette = Palette::new(&[[0, 0, 0]; 3]).unwrap();
+
+        assert_eq!(
+            source.with_palette(palette).fill(
+                PerPixel(ColorMode::Indexed1Bit),
+                &mut buffer,
+                0,
+                0,
+                7,
+                2
+            ),
+            Err(CoreError::InvalidPalette {
+                given: 3,
+                maximum: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_pixel() {
+        assert_eq!(
+            ColorMode::RGB565BE.decode_pixel(0xF800, None),
+            Ok([0xFF, 0x00, 0x00])
+        );
+        assert_eq!(
+            ColorMode::Grayscale2Bit.decode_pixel(0b10, None),
+            Ok([0xAA, 0xAA, 0xAA])
+        );
+        assert_eq!(
+            ColorMode::Indexed4Bit.decode_pixel(1, None),
+            Err(CoreError::MissingPalette)
+        );
+
+        let palette = Palette::new(&[[1, 2, 3], [4, 5, 6]]).unwrap();
+
+        assert_eq!(
+            ColorMode::Indexed4Bit.decode_pixel(1, Some(&palette)),
+            Ok([4, 5, 6])
+        );
+        assert_eq!(
+            ColorMode::Indexed4Bit.decode_pixel(7, Some(&palette)),
+            Ok([0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let pixels: [[u8; 3]; 3] = [[0xFF, 0x00, 0x00], [0x08, 0x10, 0xF8], [0x00, 0x00, 0x00]];
+        let mut buffer = [0u8; 6];
+
+        PixelSlice::new(&pixels, 3)
+            .fill(Xrgb1555, &mut buffer, 0, 0, 3, 1)
+            .unwrap();
+        assert_eq!(buffer, [0x00, 0xFC, 0x5F, 0x84, 0x00, 0x80]);
+
+        let decoded = Decoder::new(Xrgb1555)
+            .decode(&buffer, 3, 1)
+            .unwrap()
+            .into_pixels();
+
+        assert_eq!(decoded, [[0xF8, 0x00, 0x00], [0x08, 0x10, 0xF8], [0, 0, 0]]);
+    }
+
+    /// A RGB format of `BITS` bits per component, stored in the given number of pixels per
+    /// group.
+    #[derive(Clone, Copy)]
+    struct Deep<const BITS: u32>(StorageMode);
+
+    impl<const BITS: u32> PixelFormat for Deep<BITS> {
+        fn storage(&self) -> StorageMode {
+            self.0
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            pixel: C,
+            _options: &FillOptions,
+            _palette: Option<&Palette>,
+            _position: (u32, u32),
+        ) -> CoreResult<u64> {
+            let [red, green, blue] = pixel.components().map(|component| component.scale(BITS));
+
+            Ok((red as u64) << (2 * BITS) | (green as u64) << BITS | blue as u64)
+        }
+
+        fn decode_pixel(&self, code: u64, _palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            Ok([2 * BITS, BITS, 0].map(|shift| (code >> (shift + BITS - 8)) as u8))
+        }
+    }
+
+    #[test]
+    fn test_deep_format() {
+        let pixels: [[u8; 3]; 3] = [[0xFF, 0x00, 0x80], [0x12, 0x34, 0x56], [0x01, 0xFE, 0x7F]];
+        let source = PixelSlice::new(&pixels, 3);
+
+        // 48 bits per pixel
+        let format = Deep::<16>(StorageMode::BytesPerPixel(6));
+        let mut buffer = [0u8; 18];
+
+        source.fill(format, &mut buffer, 0, 0, 3, 1).unwrap();
+        assert_eq!(
+            buffer[..12],
+            [0xFF, 0xFF, 0x00, 0x00, 0x80, 0x80, 0x12, 0x12, 0x34, 0x34, 0x56, 0x56]
+        );
+
+        let decoded = Decoder::new(format).decode(&buffer, 3, 1).unwrap();
+
+        assert_eq!(decoded.pixels(), pixels);
+
+        // 2 pixels of 36 bits in 9 bytes
+        let format = Deep::<12>(StorageMode::PixelGroup {
+            pixels: 2,
+            bytes: 9,
+        });
+        let mut buffer = [0u8; 18];
+
+        source.fill(format, &mut buffer, 0, 0, 3, 1).unwrap();
+        assert_eq!(buffer[..5], [0xFF, 0xF0, 0x00, 0x80, 0x81]);
+        assert_eq!(buffer[12..], [0x7F, 0x70, 0x00, 0x00, 0x00, 0x00]);
+
+        let decoded = Decoder::new(format).decode(&buffer, 3, 1).unwrap();
+
+        assert_eq!(decoded.pixels(), pixels);
+    }
+
+    /// A 2 bits grayscale of 3 pixels per byte, the 2 least significant bits being unused.
+    struct Gray3PerByte;
+
+    impl PixelFormat for Gray3PerByte {
+        fn storage(&self) -> StorageMode {
+            StorageMode::PixelGroup {
+                pixels: 3,
+                bytes: 1,
+            }
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            pixel: C,
+            _options: &FillOptions,
+            _palette: Option<&Palette>,
+            _position: (u32, u32),
+        ) -> CoreResult<u64> {
+            let [red, _, _] = pixel.components();
+
+            Ok(red.scale(2) as u64)
+        }
+
+        fn decode_pixel(&self, code: u64, _palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            Ok([code as u8 * 0x55; 3])
+        }
+    }
+
+    #[test]
+    fn test_partial_group() {
+        let pixels = [0xFF, 0x55, 0xAA, 0xFF].map(|level| [level; 3]);
+        let mut buffer = [0u8; 2];
+
+        PixelSlice::new(&pixels, 4)
+            .fill(Gray3PerByte, &mut buffer, 0, 0, 4, 1)
+            .unwrap();
+
+        // The codes begin on the most significant bits
+        assert_eq!(buffer, [0b11_01_10_00, 0b11_00_00_00]);
+
+        let decoded = Decoder::new(Gray3PerByte).decode(&buffer, 4, 1).unwrap();
+
+        assert_eq!(decoded.pixels(), pixels);
+    }
+
+    /// A format of any storage, encoding every pixel as zero.
+    struct Storage(StorageMode);
+
+    impl PixelFormat for Storage {
+        fn storage(&self) -> StorageMode {
+            self.0
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            _pixel: C,
+            _options: &FillOptions,
+            _palette: Option<&Palette>,
+            _position: (u32, u32),
+        ) -> CoreResult<u64> {
+            Ok(0)
+        }
+
+        fn decode_pixel(&self, _code: u64, _palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            Ok([0, 0, 0])
+        }
+    }
+
+    #[test]
+    fn test_unsupported_storage() {
+        let pixels = pixels();
+        let source = PixelSlice::new(&pixels, 7);
+
+        for storage in [
+            StorageMode::BytesPerPixel(9),
+            StorageMode::PixelsPerByte(0),
+            StorageMode::PixelGroup {
+                pixels: 0,
+                bytes: 2,
+            },
+            StorageMode::PixelGroup {
+                pixels: 17,
+                bytes: 2,
+            },
+            StorageMode::PixelGroup {
+                pixels: 17,
+                bytes: 17,
+            },
+        ] {
+            let error = || Err(CoreError::UnsupportedStorage { storage });
+            let mut buffer = [0xAAu8; 12];
+
+            assert_eq!(
+                source.fill(Storage(storage), &mut buffer, 0, 0, 2, 1),
+                error(),
+                "{storage:?}"
+            );
+            assert_eq!(
+                source.fill_strided(
+                    Storage(storage),
+                    &mut buffer,
+                    TargetLayout::new(6),
+                    0,
+                    0,
+                    2,
+                    2
+                ),
+                error(),
+                "{storage:?}"
+            );
+            assert_eq!(buffer, [0xAA; 12]);
+            assert_eq!(
+                Decoder::new(Storage(storage))
+                    .decode(&buffer, 2, 1)
+                    .map(|_| ()),
+                error(),
+                "{storage:?}"
+            );
+        }
+    }
+}
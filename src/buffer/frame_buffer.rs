@@ -0,0 +1,424 @@
+use super::{Color, IntoPixelIter};
+use crate::{CoreError, CoreResult};
+
+/// Image stored in memory, row after row.
+///
+/// A reference to a frame buffer is a pixel source giving exactly the requested area, so a part
+/// of the image can be sent to a screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameBuffer<C> {
+    width: u16,
+    height: u16,
+    pixels: Vec<C>,
+}
+
+/// Iterator over an area of a [`FrameBuffer`] or a [`PixelSlice`](super::PixelSlice), row after
+/// row.
+pub struct FrameBufferIter<'a, C> {
+    rows: std::iter::Take<std::slice::Chunks<'a, C>>,
+    row: std::slice::Iter<'a, C>,
+    left: usize,
+    right: usize,
+}
+
+impl<C: Clone> FrameBuffer<C> {
+    /// Creates a frame buffer of the given dimensions filled with one color.
+    pub fn new(width: u16, height: u16, color: C) -> FrameBuffer<C> {
+        FrameBuffer {
+            width,
+            height,
+            pixels: vec![color; width as usize * height as usize],
+        }
+    }
+
+    pub fn clear(&mut self, color: C) {
+        self.pixels.fill(color);
+    }
+}
+
+impl<C> FrameBuffer<C> {
+    /// Creates a frame buffer from its pixels, given row after row.
+    pub fn from_pixels(width: u16, height: u16, pixels: Vec<C>) -> CoreResult<FrameBuffer<C>> {
+        let expected = width as usize * height as usize;
+
+        if pixels.len() != expected {
+            return Err(CoreError::BufferSizeError {
+                given: pixels.len(),
+                expected,
+            });
+        }
+
+        Ok(FrameBuffer {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[C] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<C> {
+        self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u16, y: u16) -> Option<&C> {
+        if x < self.width && y < self.height {
+            self.pixels.get(self.index(x, y))
+        } else {
+            None
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u16, y: u16, color: C) -> CoreResult<()> {
+        self.check_area(x, y, 1, 1)?;
+
+        let index = self.index(x, y);
+
+        self.pixels[index] = color;
+
+        Ok(())
+    }
+
+    const fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        check_frame_area(
+            self.width as usize,
+            self.height as usize,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+}
+
+/// Checks that an area lies within an image of the given dimensions.
+pub(super) fn check_frame_area(
+    frame_width: usize,
+    frame_height: usize,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    if x as usize + width as usize <= frame_width && y as usize + height as usize <= frame_height {
+        Ok(())
+    } else {
+        Err(CoreError::AreaOutOfBounds {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+}
+
+impl<C: Copy> FrameBuffer<C> {
+    /// Copies an area of the frame to another position. The areas may overlap.
+    pub fn copy_rect(
+        &mut self,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        to_x: u16,
+        to_y: u16,
+    ) -> CoreResult<()> {
+        self.check_area(x, y, width, height)?;
+        self.check_area(to_x, to_y, width, height)?;
+
+        let mut copy_row = |row: u16| {
+            let from = self.index(x, y + row);
+            let to = self.index(to_x, to_y + row);
+
+            self.pixels.copy_within(from..from + width as usize, to);
+        };
+
+        // The rows not copied yet must not be overwritten
+        if to_y > y {
+            (0..height).rev().for_each(&mut copy_row);
+        } else {
+            (0..height).for_each(&mut copy_row);
+        }
+
+        Ok(())
+    }
+
+    /// Copies an area of another frame to the given position.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_rect_from(
+        &mut self,
+        source: &FrameBuffer<C>,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        to_x: u16,
+        to_y: u16,
+    ) -> CoreResult<()> {
+        source.check_area(x, y, width, height)?;
+        self.check_area(to_x, to_y, width, height)?;
+
+        for row in 0..height {
+            let from = source.index(x, y + row);
+            let to = self.index(to_x, to_y + row);
+
+            self.pixels[to..to + width as usize]
+                .copy_from_slice(&source.pixels[from..from + width as usize]);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, C: Color + Copy> IntoPixelIter for &'a FrameBuffer<C> {
+    type IntoIter = FrameBufferIter<'a, C>;
+    type Item = C;
+
+    /// Gives the pixels of the area, or none when the area goes past the frame.
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        FrameBufferIter::new(&self.pixels, self.width, x, y, width, height)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        FrameBuffer::check_area(self, x, y, width, height)
+    }
+}
+
+impl<'a, C> FrameBufferIter<'a, C> {
+    /// Walks an area of an image stored row after row in a slice. An area going past the image
+    /// gives no pixels, since its rows cut at the edge would shift the following ones. A last
+    /// incomplete row is ignored.
+    pub(super) fn new(
+        pixels: &'a [C],
+        frame_width: u16,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> FrameBufferIter<'a, C> {
+        let frame_width = frame_width as usize;
+        let frame_height = pixels.len().checked_div(frame_width).unwrap_or(0);
+        let (top, height) = match check_frame_area(frame_width, frame_height, x, y, width, height) {
+            Ok(()) => (y as usize, height as usize),
+            Err(_) => (0, 0),
+        };
+
+        FrameBufferIter {
+            rows: pixels[top * frame_width..frame_height * frame_width]
+                .chunks(frame_width.max(1))
+                .take(height),
+            row: [].iter(),
+            left: x as usize,
+            right: x as usize + width as usize,
+        }
+    }
+}
+
+impl<'a, C: Copy> Iterator for FrameBufferIter<'a, C> {
+    type Item = C;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pixel) = self.row.next() {
+                return Some(*pixel);
+            }
+
+            self.row = self.rows.next()?[self.left..self.right].iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameBuffer;
+    use crate::{Buffer, Color, CoreError, FillOptions, IntoPixelIter};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl Color for Gray {
+        type Component = u8;
+
+        fn components(self) -> [u8; 3] {
+            [self.0, self.0, self.0]
+        }
+    }
+
+    /// Frame where each pixel holds its position, `0xYX`.
+    fn frame() -> FrameBuffer<Gray> {
+        let pixels = (0..4).flat_map(|y| (0..5).map(move |x| Gray(y << 4 | x)));
+
+        FrameBuffer::from_pixels(5, 4, pixels.collect()).unwrap()
+    }
+
+    fn area(frame: &FrameBuffer<Gray>, x: u16, y: u16, width: u16, height: u16) -> Vec<u8> {
+        frame
+            .into_pixel_iter(x, y, width, height)
+            .map(|pixel| pixel.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_new() {
+        let frame = FrameBuffer::new(3, 2, Gray(7));
+
+        assert_eq!(frame.pixels(), [Gray(7); 6]);
+        assert_eq!(
+            FrameBuffer::from_pixels(3, 2, vec![Gray(0); 5]),
+            Err(CoreError::BufferSizeError {
+                given: 5,
+                expected: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_pixels() {
+        let mut frame = frame();
+
+        assert_eq!(frame.get_pixel(4, 3), Some(&Gray(0x34)));
+        assert_eq!(frame.get_pixel(5, 0), None);
+        assert_eq!(frame.get_pixel(0, 4), None);
+
+        frame.set_pixel(2, 1, Gray(0xFF)).unwrap();
+        assert_eq!(frame.get_pixel(2, 1), Some(&Gray(0xFF)));
+        assert_eq!(
+            frame.set_pixel(5, 1, Gray(0xFF)),
+            Err(CoreError::AreaOutOfBounds {
+                x: 5,
+                y: 1,
+                width: 1,
+                height: 1
+            })
+        );
+
+        frame.clear(Gray(1));
+        assert_eq!(frame.into_pixels(), vec![Gray(1); 20]);
+    }
+
+    #[test]
+    fn test_into_pixel_iter() {
+        let frame = frame();
+
+        assert_eq!(
+            area(&frame, 0, 0, 5, 4),
+            (0..20)
+                .map(|i| ((i / 5) << 4) | (i % 5))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            area(&frame, 1, 2, 3, 2),
+            [0x21, 0x22, 0x23, 0x31, 0x32, 0x33]
+        );
+        assert_eq!(area(&frame, 4, 3, 1, 1), [0x34]);
+        assert_eq!(area(&frame, 1, 1, 0, 2), []);
+
+        // Going past the frame
+        assert_eq!(area(&frame, 3, 2, 4, 4), []);
+        assert_eq!(area(&frame, 6, 0, 2, 2), []);
+        assert_eq!(area(&frame, 0, 5, 2, 2), []);
+
+        let mut buffer = [0u8; 4];
+
+        frame.fill_grayscale_8bit(&mut buffer, 2, 1, 2, 2).unwrap();
+        assert_eq!(buffer, [0x12, 0x13, 0x22, 0x23]);
+    }
+
+    #[test]
+    fn test_fill_out_of_bounds() {
+        let frame = frame();
+        let mut buffer = [0xEEu8; 4];
+
+        assert_eq!(
+            frame.fill_grayscale_8bit(&mut buffer, 4, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 4,
+                y: 0,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(
+            frame
+                .with_options(FillOptions::new())
+                .fill_grayscale_8bit(&mut buffer, 0, 3, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 0,
+                y: 3,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(buffer, [0xEE; 4]);
+
+        frame.fill_grayscale_8bit(&mut buffer, 3, 2, 2, 2).unwrap();
+        assert_eq!(buffer, [0x23, 0x24, 0x33, 0x34]);
+    }
+
+    #[test]
+    fn test_copy_rect() {
+        let mut frame = frame();
+
+        // Overlapping areas, downward and upward
+        frame.copy_rect(0, 0, 3, 3, 1, 1).unwrap();
+        assert_eq!(
+            area(&frame, 0, 0, 5, 4)[5..],
+            [
+                0x10, 0x00, 0x01, 0x02, 0x14, 0x20, 0x10, 0x11, 0x12, 0x24, 0x30, 0x20, 0x21, 0x22,
+                0x34
+            ]
+        );
+
+        let mut frame = self::frame();
+
+        frame.copy_rect(1, 1, 3, 3, 0, 0).unwrap();
+        assert_eq!(
+            area(&frame, 0, 0, 3, 3),
+            [0x11, 0x12, 0x13, 0x21, 0x22, 0x23, 0x31, 0x32, 0x33]
+        );
+
+        assert_eq!(
+            frame.copy_rect(0, 0, 3, 3, 3, 0),
+            Err(CoreError::AreaOutOfBounds {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 3
+            })
+        );
+
+        let mut target = FrameBuffer::new(3, 2, Gray(0));
+
+        target
+            .copy_rect_from(&self::frame(), 3, 2, 2, 2, 1, 0)
+            .unwrap();
+        assert_eq!(
+            target.pixels(),
+            [
+                Gray(0),
+                Gray(0x23),
+                Gray(0x24),
+                Gray(0),
+                Gray(0x33),
+                Gray(0x34)
+            ]
+        );
+        assert!(target
+            .copy_rect_from(&self::frame(), 4, 2, 2, 2, 1, 0)
+            .is_err());
+    }
+}
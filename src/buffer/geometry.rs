@@ -0,0 +1,568 @@
+use super::frame_buffer::check_frame_area;
+use super::{ColorMode, FillOptions, IntoPixelIter, Palette};
+use crate::CoreResult;
+
+/// Pixel source giving a rectangle of another source, its top left corner being the origin.
+///
+/// An area going past the rectangle gives no pixels, see [`IntoPixelIter::check_area`]. Built by
+/// [`IntoPixelIter::crop`].
+#[derive(Clone, Copy, Debug)]
+pub struct Crop<T> {
+    source: T,
+    left: u16,
+    top: u16,
+    width: u16,
+    height: u16,
+}
+
+/// Mirroring or rotation by a multiple of 90° of a frame.
+///
+/// The rotations are clockwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transform {
+    FlipHorizontal,
+    FlipVertical,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors the frame along its diagonal from the top left corner, swapping the rows and the
+    /// columns.
+    Transpose,
+}
+
+/// Pixel source giving a frame of another source mirrored or rotated.
+///
+/// The transformation needs the dimensions of the source frame, an area going past the
+/// transformed frame giving no pixels. The pixels of an area are gathered from the source before
+/// being given in their new order, only the native rows of [`Transform::FlipVertical`] being
+/// copied straight from the source. Built by [`IntoPixelIter::transform`].
+#[derive(Clone, Copy, Debug)]
+pub struct Transformed<T> {
+    source: T,
+    transform: Transform,
+    source_width: u16,
+    source_height: u16,
+}
+
+/// Iterator over an area of a [`Transformed`] source.
+pub struct TransformedIter<C> {
+    pixels: Vec<C>,
+    transform: Transform,
+    source_width: u16,
+    source_height: u16,
+    // Area of the source holding the pixels gathered
+    source_left: u16,
+    source_top: u16,
+    source_stride: usize,
+    left: u16,
+    right: u16,
+    bottom: u16,
+    x: u16,
+    y: u16,
+}
+
+impl<T> Crop<T> {
+    pub const fn new(source: T, x: u16, y: u16, width: u16, height: u16) -> Crop<T> {
+        Crop {
+            source,
+            left: x,
+            top: y,
+            width,
+            height,
+        }
+    }
+
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    /// Gives the area of the source matching an area of the rectangle, or `None` when the area
+    /// goes past the rectangle.
+    fn source_area(&self, x: u16, y: u16, width: u16, height: u16) -> Option<(u16, u16, u16, u16)> {
+        self.check_bounds(x, y, width, height).ok()?;
+
+        Some((
+            self.left.saturating_add(x),
+            self.top.saturating_add(y),
+            width,
+            height,
+        ))
+    }
+
+    fn check_bounds(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        check_frame_area(
+            self.width as usize,
+            self.height as usize,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+}
+
+impl<T: IntoPixelIter> IntoPixelIter for Crop<T> {
+    type IntoIter = T::IntoIter;
+    type Item = T::Item;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        let (x, y, width, height) = self
+            .source_area(x, y, width, height)
+            .unwrap_or((self.left, self.top, 0, 0));
+
+        self.source.into_pixel_iter(x, y, width, height)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        self.check_bounds(x, y, width, height)?;
+
+        self.source.check_area(
+            self.left.saturating_add(x),
+            self.top.saturating_add(y),
+            width,
+            height,
+        )
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        self.source.fill_options()
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        self.source.palette()
+    }
+
+    fn native_mode(&self) -> Option<ColorMode> {
+        self.source.native_mode()
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        let (x, y, width, _) = self.source_area(x, y, width, 1)?;
+
+        self.source.native_row(x, y, width)
+    }
+}
+
+impl Transform {
+    /// Gives the dimensions of a frame once transformed.
+    pub const fn dimensions(&self, width: u16, height: u16) -> (u16, u16) {
+        match self {
+            Transform::FlipHorizontal | Transform::FlipVertical | Transform::Rotate180 => {
+                (width, height)
+            }
+            Transform::Rotate90 | Transform::Rotate270 | Transform::Transpose => (height, width),
+        }
+    }
+
+    /// Gives the position in the source frame of a pixel of the transformed frame.
+    const fn source_position(&self, x: u16, y: u16, width: u16, height: u16) -> (u16, u16) {
+        match self {
+            Transform::FlipHorizontal => (width - 1 - x, y),
+            Transform::FlipVertical => (x, height - 1 - y),
+            Transform::Rotate90 => (y, height - 1 - x),
+            Transform::Rotate180 => (width - 1 - x, height - 1 - y),
+            Transform::Rotate270 => (width - 1 - y, x),
+            Transform::Transpose => (y, x),
+        }
+    }
+}
+
+impl<T> Transformed<T> {
+    pub const fn new(source: T, transform: Transform, width: u16, height: u16) -> Transformed<T> {
+        Transformed {
+            source,
+            transform,
+            source_width: width,
+            source_height: height,
+        }
+    }
+
+    /// Gives the width of the transformed frame.
+    pub const fn width(&self) -> u16 {
+        self.transform
+            .dimensions(self.source_width, self.source_height)
+            .0
+    }
+
+    /// Gives the height of the transformed frame.
+    pub const fn height(&self) -> u16 {
+        self.transform
+            .dimensions(self.source_width, self.source_height)
+            .1
+    }
+
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    const fn source_position(&self, x: u16, y: u16) -> (u16, u16) {
+        self.transform
+            .source_position(x, y, self.source_width, self.source_height)
+    }
+
+    fn check_bounds(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        check_frame_area(
+            self.width() as usize,
+            self.height() as usize,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+
+    /// Gives the area of the source holding the pixels of a non empty area of the transformed
+    /// frame.
+    fn source_area(&self, x: u16, y: u16, width: u16, height: u16) -> (u16, u16, u16, u16) {
+        // The opposite corners of the area stay opposite once transformed
+        let first = self.source_position(x, y);
+        let last = self.source_position(x + width - 1, y + height - 1);
+
+        (
+            first.0.min(last.0),
+            first.1.min(last.1),
+            first.0.abs_diff(last.0) + 1,
+            first.1.abs_diff(last.1) + 1,
+        )
+    }
+}
+
+impl<T> IntoPixelIter for Transformed<T>
+where
+    T: IntoPixelIter,
+    T::Item: Copy,
+{
+    type IntoIter = TransformedIter<T::Item>;
+    type Item = T::Item;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        let (right, bottom) = match self.check_bounds(x, y, width, height) {
+            Ok(()) => (x + width, y + height),
+            Err(_) => (x, y),
+        };
+
+        let mut iter = TransformedIter {
+            pixels: Vec::new(),
+            transform: self.transform,
+            source_width: self.source_width,
+            source_height: self.source_height,
+            source_left: 0,
+            source_top: 0,
+            source_stride: 0,
+            left: x,
+            right,
+            bottom,
+            x,
+            y,
+        };
+
+        if x == right || y == bottom {
+            return iter;
+        }
+
+        let (source_left, source_top, source_width, source_height) =
+            self.source_area(x, y, width, height);
+
+        iter.pixels = self
+            .source
+            .into_pixel_iter(source_left, source_top, source_width, source_height)
+            .collect();
+        iter.source_left = source_left;
+        iter.source_top = source_top;
+        iter.source_stride = source_width as usize;
+
+        iter
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        self.check_bounds(x, y, width, height)?;
+
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let (x, y, width, height) = self.source_area(x, y, width, height);
+
+        self.source.check_area(x, y, width, height)
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        self.source.fill_options()
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        self.source.palette()
+    }
+
+    // Only the vertical flip keeps the pixels of a row together
+    fn native_mode(&self) -> Option<ColorMode> {
+        match self.transform {
+            Transform::FlipVertical => self.source.native_mode(),
+            _ => None,
+        }
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        match self.transform {
+            Transform::FlipVertical if y < self.source_height => {
+                self.source.native_row(x, self.source_height - 1 - y, width)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<C: Copy> Iterator for TransformedIter<C> {
+    type Item = C;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == self.right || self.y >= self.bottom {
+            return None;
+        }
+
+        let (x, y) =
+            self.transform
+                .source_position(self.x, self.y, self.source_width, self.source_height);
+        let index =
+            (y - self.source_top) as usize * self.source_stride + (x - self.source_left) as usize;
+
+        self.x += 1;
+
+        if self.x == self.right {
+            self.x = self.left;
+            self.y += 1;
+        }
+
+        // A source ending early ends the area at its first missing pixel
+        self.pixels.get(index).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::{Buffer, CoreError, FrameBuffer, IntoPixelIter, PixelSlice};
+
+    /// Gives a frame whose pixels hold their position, as `[x, y, 0]`.
+    fn frame(width: u16, height: u16) -> FrameBuffer<[u8; 3]> {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x as u8, y as u8, 0]))
+            .collect();
+
+        FrameBuffer::from_pixels(width, height, pixels).unwrap()
+    }
+
+    fn positions<T: IntoPixelIter<Item = [u8; 3]>>(
+        source: T,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Vec<(u8, u8)> {
+        source
+            .into_pixel_iter(x, y, width, height)
+            .map(|[x, y, _]| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn test_crop() {
+        let frame = frame(5, 4);
+        let crop = (&frame).crop(1, 1, 3, 2);
+
+        assert_eq!((crop.width(), crop.height()), (3, 2));
+        assert_eq!(
+            positions(crop, 0, 0, 3, 2),
+            [(1, 1), (2, 1), (3, 1), (1, 2), (2, 2), (3, 2)]
+        );
+        assert_eq!(positions(crop, 1, 1, 2, 1), [(2, 2), (3, 2)]);
+
+        // Going past the rectangle
+        assert!(positions(crop, 2, 1, 5, 5).is_empty());
+        assert!(positions(crop, 3, 0, 1, 1).is_empty());
+        assert!(positions(crop, 0, 2, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn test_transform() {
+        let frame = frame(3, 2);
+        let cases = [
+            (
+                Transform::FlipHorizontal,
+                vec![(2, 0), (1, 0), (0, 0), (2, 1), (1, 1), (0, 1)],
+            ),
+            (
+                Transform::FlipVertical,
+                vec![(0, 1), (1, 1), (2, 1), (0, 0), (1, 0), (2, 0)],
+            ),
+            (
+                Transform::Rotate90,
+                vec![(0, 1), (0, 0), (1, 1), (1, 0), (2, 1), (2, 0)],
+            ),
+            (
+                Transform::Rotate180,
+                vec![(2, 1), (1, 1), (0, 1), (2, 0), (1, 0), (0, 0)],
+            ),
+            (
+                Transform::Rotate270,
+                vec![(2, 0), (2, 1), (1, 0), (1, 1), (0, 0), (0, 1)],
+            ),
+            (
+                Transform::Transpose,
+                vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)],
+            ),
+        ];
+
+        for (transform, expected) in cases {
+            let transformed = (&frame).transform(transform, 3, 2);
+            let (width, height) = transform.dimensions(3, 2);
+
+            assert_eq!((transformed.width(), transformed.height()), (width, height));
+            assert_eq!(
+                positions(transformed, 0, 0, width, height),
+                expected,
+                "{transform:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_transform_area() {
+        let frame = frame(5, 4);
+
+        for transform in [
+            Transform::FlipHorizontal,
+            Transform::FlipVertical,
+            Transform::Rotate90,
+            Transform::Rotate180,
+            Transform::Rotate270,
+            Transform::Transpose,
+        ] {
+            let transformed = (&frame).transform(transform, 5, 4);
+            let whole = positions(transformed, 0, 0, transformed.width(), transformed.height());
+            let (x, y, width, height) = (1, 2, 2, 2);
+            let expected = (y..y + height)
+                .flat_map(|row| {
+                    let start = (row * transformed.width() + x) as usize;
+
+                    whole[start..start + width as usize].to_vec()
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                positions(transformed, x, y, width, height),
+                expected,
+                "{transform:?}"
+            );
+
+            // Going past the transformed frame
+            assert!(positions(transformed, 0, 0, 100, 100).is_empty());
+            assert!(positions(transformed, transformed.width(), 0, 1, 1).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let frame = frame(5, 4);
+
+        // Two quarter turns make a half turn, a flip cancels itself
+        let turned =
+            (&frame)
+                .transform(Transform::Rotate90, 5, 4)
+                .transform(Transform::Rotate90, 4, 5);
+        let flipped = (&frame)
+            .transform(Transform::FlipHorizontal, 5, 4)
+            .transform(Transform::FlipHorizontal, 5, 4);
+
+        assert_eq!(
+            positions(turned, 1, 1, 3, 2),
+            positions((&frame).transform(Transform::Rotate180, 5, 4), 1, 1, 3, 2)
+        );
+        assert_eq!(
+            positions(flipped, 0, 0, 5, 4),
+            positions(&frame, 0, 0, 5, 4)
+        );
+
+        // Cropping the rotated frame
+        let cropped = (&frame)
+            .transform(Transform::Rotate270, 5, 4)
+            .crop(1, 2, 2, 2);
+
+        assert_eq!(
+            positions(cropped, 0, 0, 2, 2),
+            [(2, 1), (2, 2), (1, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let pixels: [[u8; 3]; 4] = [[0xFF, 0, 0], [0, 0xFF, 0], [0, 0, 0xFF], [0xFF; 3]];
+        let source = PixelSlice::new(&pixels, 2).transform(Transform::Rotate90, 2, 2);
+        let mut buffer = [0u8; 12];
+
+        source.fill_rgb(&mut buffer, 0, 0, 2, 2).unwrap();
+
+        assert_eq!(
+            buffer,
+            [0, 0, 0xFF, 0xFF, 0, 0, 0xFF, 0xFF, 0xFF, 0, 0xFF, 0]
+        );
+    }
+
+    #[test]
+    fn test_fill_out_of_bounds() {
+        let frame = frame(5, 4);
+        let mut buffer = [0xEEu8; 12];
+
+        assert_eq!(
+            (&frame).crop(1, 1, 3, 2).fill_rgb(&mut buffer, 2, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 2,
+                y: 0,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(
+            (&frame)
+                .transform(Transform::Rotate90, 5, 4)
+                .fill_rgb(&mut buffer, 3, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 3,
+                y: 0,
+                width: 2,
+                height: 2
+            })
+        );
+
+        // A rectangle going past the source
+        assert_eq!(
+            (&frame).crop(4, 3, 2, 2).fill_rgb(&mut buffer, 0, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 4,
+                y: 3,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(
+            (&frame)
+                .transform(Transform::FlipVertical, 5, 5)
+                .fill_rgb(&mut buffer, 0, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 0,
+                y: 3,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(buffer, [0xEE; 12]);
+    }
+}
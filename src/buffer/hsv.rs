@@ -0,0 +1,160 @@
+use super::Color;
+
+/// Color given by its hue, saturation and value.
+///
+/// The hue is in degrees and wraps around, the saturation and the value range from 0 to 1 as the
+/// `f32` components. A NaN hue is taken as 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hsv {
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+}
+
+/// Color given by its hue, saturation and lightness.
+///
+/// The hue is in degrees and wraps around, the saturation and the lightness range from 0 to 1 as
+/// the `f32` components. A NaN hue is taken as 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hsl {
+    pub hue: f32,
+    pub saturation: f32,
+    pub lightness: f32,
+}
+
+impl Hsv {
+    pub const fn new(hue: f32, saturation: f32, value: f32) -> Hsv {
+        Hsv {
+            hue,
+            saturation,
+            value,
+        }
+    }
+}
+
+impl Hsl {
+    pub const fn new(hue: f32, saturation: f32, lightness: f32) -> Hsl {
+        Hsl {
+            hue,
+            saturation,
+            lightness,
+        }
+    }
+}
+
+/// Gives the red, green and blue components of a fully saturated hue, between 0 and 1.
+fn hue_components(hue: f32) -> [f32; 3] {
+    let hue = if hue.is_finite() {
+        hue.rem_euclid(360.0) / 60.0
+    } else {
+        0.0
+    };
+
+    // Distance to the hue of each primary color, each being saturated one third of the wheel
+    [0.0, 4.0, 2.0].map(|primary: f32| {
+        let distance = (hue + primary).rem_euclid(6.0);
+
+        (distance - 3.0).abs().clamp(1.0, 2.0) - 1.0
+    })
+}
+
+fn clamp_unit(value: f32) -> f32 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+impl Color for Hsv {
+    type Component = f32;
+
+    fn components(self) -> [f32; 3] {
+        let saturation = clamp_unit(self.saturation);
+        let value = clamp_unit(self.value);
+
+        hue_components(self.hue).map(|level| value * (1.0 - saturation * (1.0 - level)))
+    }
+}
+
+impl Color for Hsl {
+    type Component = f32;
+
+    fn components(self) -> [f32; 3] {
+        let saturation = clamp_unit(self.saturation);
+        let lightness = clamp_unit(self.lightness);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+
+        hue_components(self.hue).map(|level| lightness + chroma * (level - 0.5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hsl, Hsv};
+    use crate::{Buffer, Color, PixelSlice};
+
+    fn rgb<C: Color>(color: C) -> [u8; 3] {
+        let mut buffer = [0u8; 3];
+
+        color.fill_rgb(&mut buffer).unwrap();
+
+        buffer
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert_eq!(rgb(Hsv::new(0.0, 1.0, 1.0)), [0xFF, 0x00, 0x00]);
+        assert_eq!(rgb(Hsv::new(60.0, 1.0, 1.0)), [0xFF, 0xFF, 0x00]);
+        assert_eq!(rgb(Hsv::new(120.0, 1.0, 1.0)), [0x00, 0xFF, 0x00]);
+        assert_eq!(rgb(Hsv::new(180.0, 1.0, 1.0)), [0x00, 0xFF, 0xFF]);
+        assert_eq!(rgb(Hsv::new(240.0, 1.0, 1.0)), [0x00, 0x00, 0xFF]);
+        assert_eq!(rgb(Hsv::new(300.0, 1.0, 1.0)), [0xFF, 0x00, 0xFF]);
+        assert_eq!(rgb(Hsv::new(30.0, 1.0, 1.0)), [0xFF, 0x80, 0x00]);
+        assert_eq!(rgb(Hsv::new(210.0, 0.5, 0.8)), [0x66, 0x99, 0xCC]);
+
+        // Gray whatever the hue
+        assert_eq!(rgb(Hsv::new(123.0, 0.0, 0.5)), [0x80; 3]);
+        assert_eq!(rgb(Hsv::new(0.0, 1.0, 0.0)), [0x00; 3]);
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(rgb(Hsl::new(0.0, 1.0, 0.5)), [0xFF, 0x00, 0x00]);
+        assert_eq!(rgb(Hsl::new(120.0, 1.0, 0.25)), [0x00, 0x80, 0x00]);
+        assert_eq!(rgb(Hsl::new(240.0, 1.0, 0.75)), [0x80, 0x80, 0xFF]);
+        assert_eq!(rgb(Hsl::new(210.0, 0.5, 0.6)), [0x66, 0x99, 0xCC]);
+        assert_eq!(rgb(Hsl::new(45.0, 0.3, 1.0)), [0xFF; 3]);
+        assert_eq!(rgb(Hsl::new(45.0, 0.3, 0.0)), [0x00; 3]);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        // The hue wraps around
+        assert_eq!(rgb(Hsv::new(360.0, 1.0, 1.0)), rgb(Hsv::new(0.0, 1.0, 1.0)));
+        assert_eq!(
+            rgb(Hsv::new(-120.0, 1.0, 1.0)),
+            rgb(Hsv::new(240.0, 1.0, 1.0))
+        );
+        assert_eq!(
+            rgb(Hsl::new(780.0, 1.0, 0.5)),
+            rgb(Hsl::new(60.0, 1.0, 0.5))
+        );
+
+        assert_eq!(rgb(Hsv::new(f32::NAN, 1.0, 1.0)), [0xFF, 0x00, 0x00]);
+        assert_eq!(rgb(Hsv::new(0.0, 2.0, f32::NAN)), [0x00; 3]);
+        assert_eq!(rgb(Hsl::new(0.0, -1.0, 2.0)), [0xFF; 3]);
+    }
+
+    #[test]
+    fn test_fill() {
+        let pixels = [Hsv::new(0.0, 1.0, 1.0), Hsv::new(240.0, 1.0, 1.0)];
+        let mut buffer = [0u8; 4];
+
+        PixelSlice::new(&pixels, 2)
+            .fill_rgb565be(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+
+        assert_eq!(buffer, [0xF8, 0x00, 0x00, 0x1F]);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{CoreError, CoreResult};
+
+/// Placement of the filled area in a larger target, such as a staging buffer holding a whole
+/// screen.
+///
+/// Each row of the area begins `stride` bytes after the previous one, the first one at `offset`.
+/// The bytes between the rows are left untouched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TargetLayout {
+    offset: usize,
+    stride: usize,
+}
+
+/// Target of a fill, holding the rows one after the other or placed by a [`TargetLayout`], see
+/// [`PixelFormat::encode`](super::PixelFormat::encode).
+pub struct FillTarget<'a> {
+    data: &'a mut [u8],
+    layout: Option<TargetLayout>,
+}
+
+impl TargetLayout {
+    pub const fn new(stride: usize) -> TargetLayout {
+        TargetLayout { offset: 0, stride }
+    }
+
+    pub const fn with_offset(mut self, offset: usize) -> TargetLayout {
+        self.offset = offset;
+
+        self
+    }
+
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub const fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Gives the target length required to hold `height` rows of `row_length` bytes, the last row
+    /// not needing to be followed by a full stride.
+    pub const fn required_length(&self, row_length: usize, height: usize) -> usize {
+        if row_length == 0 || height == 0 {
+            0
+        } else {
+            self.offset + self.stride * (height - 1) + row_length
+        }
+    }
+
+    /// Checks that the rows fit in the target. The stride is only checked when there are several
+    /// rows.
+    pub(crate) fn check(&self, target: &[u8], row_length: usize, height: usize) -> CoreResult<()> {
+        if height > 1 && self.stride < row_length {
+            return Err(CoreError::InvalidStride {
+                stride: self.stride,
+                row_length,
+            });
+        }
+
+        let expected = self.required_length(row_length, height);
+
+        if target.len() >= expected {
+            Ok(())
+        } else {
+            Err(CoreError::BufferSizeError {
+                given: target.len(),
+                expected,
+            })
+        }
+    }
+
+    /// Gives the `height` rows of `row_length` bytes of a source placed according to the layout,
+    /// checked as in [`TargetLayout::check`].
+    pub(crate) fn rows<'a>(
+        &self,
+        source: &'a [u8],
+        row_length: usize,
+        height: usize,
+    ) -> CoreResult<impl Iterator<Item = &'a [u8]>> {
+        self.check(source, row_length, height)?;
+
+        let source = source.get(self.offset..).unwrap_or_default();
+
+        Ok(source
+            .chunks(self.stride.max(row_length).max(1))
+            .take(height)
+            .map(move |row| &row[..row_length]))
+    }
+}
+
+impl<'a> FillTarget<'a> {
+    /// Creates a target holding exactly the rows, one after the other.
+    pub const fn packed(data: &'a mut [u8]) -> FillTarget<'a> {
+        FillTarget { data, layout: None }
+    }
+
+    /// Creates a target holding the rows placed according to the layout.
+    pub const fn with_layout(data: &'a mut [u8], layout: TargetLayout) -> FillTarget<'a> {
+        FillTarget {
+            data,
+            layout: Some(layout),
+        }
+    }
+
+    /// Splits `height` rows of `row_length` bytes in chunks of `size` bytes, row by row.
+    ///
+    /// Without layout, the target must exactly hold the rows. Otherwise, it must be long enough to
+    /// hold the last row, see [`TargetLayout::required_length`].
+    pub fn chunks(
+        self,
+        row_length: usize,
+        height: usize,
+        size: usize,
+    ) -> CoreResult<impl Iterator<Item = &'a mut [u8]>> {
+        Ok(self
+            .rows(row_length, height)?
+            .flat_map(move |row| row.chunks_exact_mut(size)))
+    }
+
+    /// Gives the `height` rows of `row_length` bytes of the target, checked as in
+    /// [`FillTarget::chunks`].
+    pub fn rows(
+        self,
+        row_length: usize,
+        height: usize,
+    ) -> CoreResult<impl Iterator<Item = &'a mut [u8]>> {
+        let (offset, stride) = self.placement(row_length, height)?;
+        let data = self.data.get_mut(offset..).unwrap_or_default();
+
+        Ok(data
+            .chunks_mut(stride.max(row_length).max(1))
+            .take(height)
+            .map(move |row| &mut row[..row_length]))
+    }
+
+    /// Splits the target in bands of `band_height` rows, each band being a target of its own.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn bands(
+        self,
+        row_length: usize,
+        height: usize,
+        band_height: usize,
+    ) -> CoreResult<Vec<FillTarget<'a>>> {
+        let (offset, stride) = self.placement(row_length, height)?;
+        let data = self.data.get_mut(offset..).unwrap_or_default();
+
+        Ok(data
+            .chunks_mut(stride.max(row_length).max(1) * band_height.max(1))
+            .take(height.div_ceil(band_height.max(1)))
+            .map(|band| FillTarget::with_layout(band, TargetLayout::new(stride)))
+            .collect())
+    }
+
+    /// Checks the target can hold the rows, giving the offset of the first row and the stride.
+    fn placement(&self, row_length: usize, height: usize) -> CoreResult<(usize, usize)> {
+        match self.layout {
+            None => {
+                CoreError::check_length(self.data, row_length * height)?;
+
+                Ok((0, row_length))
+            }
+            Some(layout) => {
+                layout.check(self.data, row_length, height)?;
+
+                Ok((layout.offset(), layout.stride()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FillTarget, TargetLayout};
+    use crate::CoreError;
+
+    #[test]
+    fn test_required_length() {
+        let layout = TargetLayout::new(10).with_offset(3);
+
+        assert_eq!(layout.required_length(4, 3), 27);
+        assert_eq!(layout.required_length(4, 1), 7);
+        assert_eq!(layout.required_length(4, 0), 0);
+        assert_eq!(layout.required_length(0, 3), 0);
+    }
+
+    #[test]
+    fn test_check() {
+        let layout = TargetLayout::new(10).with_offset(3);
+
+        assert_eq!(layout.check(&[0; 27], 4, 3), Ok(()));
+        assert_eq!(layout.check(&[0; 40], 4, 3), Ok(()));
+        assert_eq!(
+            layout.check(&[0; 26], 4, 3),
+            Err(CoreError::BufferSizeError {
+                given: 26,
+                expected: 27
+            })
+        );
+        assert_eq!(
+            layout.check(&[0; 40], 12, 2),
+            Err(CoreError::InvalidStride {
+                stride: 10,
+                row_length: 12
+            })
+        );
+        assert_eq!(layout.check(&[0; 40], 12, 1), Ok(()));
+    }
+
+    #[test]
+    fn test_source_rows() {
+        let data = [0, 1, 1, 0, 2, 2, 0, 3];
+        let layout = TargetLayout::new(3).with_offset(1);
+
+        assert_eq!(
+            layout.rows(&data, 2, 2).unwrap().collect::<Vec<_>>(),
+            [[1, 1], [2, 2]]
+        );
+        assert_eq!(
+            layout.rows(&data, 2, 3).err(),
+            Some(CoreError::BufferSizeError {
+                given: 8,
+                expected: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut data = [0u8; 12];
+
+        for (i, chunk) in FillTarget::with_layout(&mut data, TargetLayout::new(5).with_offset(1))
+            .chunks(4, 2, 2)
+            .unwrap()
+            .enumerate()
+        {
+            chunk.fill(i as u8 + 1);
+        }
+
+        assert_eq!(data, [0, 1, 1, 2, 2, 0, 3, 3, 4, 4, 0, 0]);
+
+        let mut data = [0u8; 8];
+
+        for (i, chunk) in FillTarget::packed(&mut data)
+            .chunks(4, 2, 2)
+            .unwrap()
+            .enumerate()
+        {
+            chunk.fill(i as u8 + 1);
+        }
+
+        assert_eq!(data, [1, 1, 2, 2, 3, 3, 4, 4]);
+
+        assert!(FillTarget::packed(&mut data).chunks(4, 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut data = [0u8; 12];
+
+        for (i, row) in FillTarget::with_layout(&mut data, TargetLayout::new(5).with_offset(1))
+            .rows(3, 2)
+            .unwrap()
+            .enumerate()
+        {
+            row.fill(i as u8 + 1);
+        }
+
+        assert_eq!(data, [0, 1, 1, 1, 0, 0, 2, 2, 2, 0, 0, 0]);
+
+        assert_eq!(FillTarget::packed(&mut []).rows(0, 3).unwrap().count(), 0);
+        assert!(FillTarget::packed(&mut data).rows(5, 2).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+/// Model used to compute the luminance of a color for the grayscale color modes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Luminance {
+    /// Takes the brightest component. A saturated blue is as bright as white.
+    #[default]
+    Max,
+    /// Takes the mean of the three components.
+    Average,
+    /// Uses the ITU-R BT.601 weights (`0.299 R + 0.587 G + 0.114 B`).
+    Rec601,
+    /// Uses the ITU-R BT.709 weights (`0.2126 R + 0.7152 G + 0.0722 B`).
+    Rec709,
+    /// Uses custom weights, divided by their sum. Null weights always give a black pixel.
+    Weighted { red: u32, green: u32, blue: u32 },
+}
+
+impl Luminance {
+    /// Gives the weights applied to the red, green and blue components.
+    ///
+    /// # Returns
+    ///
+    /// `None` for the [`Luminance::Max`] model which is not a weighted mean.
+    pub const fn weights(&self) -> Option<[u32; 3]> {
+        match self {
+            Luminance::Max => None,
+            Luminance::Average => Some([1, 1, 1]),
+            Luminance::Rec601 => Some([299, 587, 114]),
+            Luminance::Rec709 => Some([2126, 7152, 722]),
+            Luminance::Weighted { red, green, blue } => Some([*red, *green, *blue]),
+        }
+    }
+}
@@ -1,177 +1,527 @@
+mod alpha;
 mod color;
 mod color_mode;
 mod component;
+mod decoder;
+mod diffusion;
+mod dithering;
+mod format;
+mod frame_buffer;
+mod geometry;
+mod hsv;
 mod integration;
-
+mod layout;
+mod luminance;
+mod options;
+mod palette;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod pixel_slice;
+mod quantizer;
+mod resize;
+mod rgb;
+mod scanline;
+mod simd;
+mod transcode;
+mod yuv;
 
 use crate::{CoreError, CoreResult};
+pub use alpha::{AlphaColor, AlphaMode, BlendMode, BlendedColor, Composite, CompositeIter};
 pub use color::Color;
 pub use color_mode::*;
 pub use component::Component;
+pub use decoder::Decoder;
+use diffusion::Diffuser;
+pub use diffusion::DiffusionKernel;
+pub use dithering::Dithering;
+pub use format::PixelFormat;
+pub use frame_buffer::{FrameBuffer, FrameBufferIter};
+pub use geometry::{Crop, Transform, Transformed, TransformedIter};
+pub use hsv::{Hsl, Hsv};
+pub use layout::{FillTarget, TargetLayout};
+pub use luminance::Luminance;
+pub use options::{FillOptions, WithOptions};
+pub use palette::{NearestColor, Palette, WithPalette};
+#[cfg(feature = "parallel")]
+pub use parallel::PARALLEL_THRESHOLD;
+pub use pixel_slice::PixelSlice;
+pub use quantizer::Quantizer;
+pub use resize::{ResizeFilter, ResizeMode, Resized, ResizedIter};
+pub use rgb::Rgb565;
+use scanline::next_full_row;
+pub use scanline::{IterScanlines, PixelSource, Scanlines};
+pub use simd::{convert_slice, SliceFormat};
+pub use transcode::{transcode, transcode_strided};
+pub use yuv::{YuvFrame, YuvFrameIter, YuvLayout, YuvMatrix, YuvRange};
 
 const fn calculate_length(width: u16, height: u16) -> usize {
     width as usize * height as usize
 }
 
+/// Fills a buffer storing `BYTES_PER_PIXEL` bytes per pixel, converting the source row by row.
+///
+/// `convertor` encodes a pixel located at the given absolute position into its bytes.
 #[inline]
-fn generic_fill<const BYTES_PER_PIXEL: usize, C, I, T>(
-    iter: T,
-    target: &mut [u8],
+fn generic_fill<const BYTES_PER_PIXEL: usize, S: PixelSource>(
+    source: S,
+    target: FillTarget,
     x: u16,
     y: u16,
     width: u16,
     height: u16,
-    convertor: fn(C, &mut [u8]) -> CoreResult<()>,
-) -> CoreResult<()>
-where
-    C: Color,
-    I: Iterator<Item = C>,
-    T: IntoPixelIter<IntoIter = I, Item = C>,
-{
-    let length = calculate_length(width, height);
+    convertor: impl Fn(S::Pixel, &FillOptions, (u32, u32), &mut [u8]) -> CoreResult<()>,
+) -> CoreResult<()> {
+    let options = source.fill_options();
+    let buffers = target.rows(BYTES_PER_PIXEL * width as usize, height as usize)?;
+    let mut scanlines = source.into_scanlines(x, y, width, height);
+
+    for (row_y, buf) in (y as u32..).zip(buffers) {
+        let Some(row) = next_full_row(&mut scanlines, width)? else {
+            break;
+        };
+
+        for ((row_x, pixel), buf) in (x as u32..)
+            .zip(row)
+            .zip(buf.chunks_exact_mut(BYTES_PER_PIXEL))
+        {
+            convertor(pixel, &options, (row_x, row_y), buf)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gives the level of a pixel in a grayscale using `BITS` bits per pixel.
+#[inline]
+fn grayscale_level<C: Color, const BITS: usize>(
+    pixel: C,
+    options: &FillOptions,
+    (x, y): (u32, u32),
+) -> u32 {
+    let luminance = pixel.luminance_with(options.luminance());
+
+    match options.dithering().threshold(x, y) {
+        Some(threshold) => Quantizer::Threshold(threshold).quantize(luminance, BITS as u32) as u32,
+        None => luminance.scale(BITS as u32),
+    }
+}
 
-    CoreError::check_length(target, BYTES_PER_PIXEL * length)?;
+/// Writes the pixels in a buffer storing a group of `PIXELS` pixels in `BYTES` bytes.
+///
+/// The pixels are stored from the most significant bits to the least significant ones, and the
+/// bytes of a group in big endian. Depending on the [`SubpixelPacking`], a group never holds
+/// pixels of two different rows or the whole image is packed as one single row.
+struct PixelGroupWriter<'a, const PIXELS: usize, const BYTES: usize> {
+    groups: Box<dyn Iterator<Item = &'a mut [u8]> + 'a>,
+    row_length: usize,
+    group: u32,
+    pixels: usize,
+    column: usize,
+}
 
-    let mut begin = 0;
-    let mut iterator = iter.into_pixel_iter(x, y, width, height);
+impl<'a, const PIXELS: usize, const BYTES: usize> PixelGroupWriter<'a, PIXELS, BYTES> {
+    const BITS: usize = 8 * BYTES / PIXELS;
 
-    while let Some(pixel) = iterator.next() {
-        let end = begin + BYTES_PER_PIXEL;
+    const STORAGE: StorageMode = if BYTES == 1 {
+        StorageMode::PixelsPerByte(PIXELS)
+    } else {
+        StorageMode::PixelGroup {
+            pixels: PIXELS,
+            bytes: BYTES,
+        }
+    };
 
-        convertor(pixel, &mut target[begin..end])?;
+    fn new(
+        target: FillTarget<'a>,
+        width: u16,
+        height: u16,
+        packing: SubpixelPacking,
+    ) -> CoreResult<Self> {
+        // The whole image is one single row when packed continuously
+        let (row_length, rows, row_bytes) = match packing {
+            SubpixelPacking::RowPadded => (
+                width as usize,
+                height as usize,
+                Self::STORAGE.buffer_length(width, 1, packing),
+            ),
+            SubpixelPacking::Continuous => (
+                calculate_length(width, height),
+                1,
+                Self::STORAGE.buffer_length(width, height, packing),
+            ),
+        };
 
-        begin = end;
+        Ok(PixelGroupWriter {
+            groups: Box::new(target.chunks(row_bytes, rows, BYTES)?),
+            row_length,
+            group: 0,
+            pixels: 0,
+            column: 0,
+        })
+    }
+
+    fn push(&mut self, level: u32) {
+        self.group = self.group << Self::BITS | level;
+        self.pixels += 1;
+        self.column += 1;
+
+        if self.pixels == PIXELS || self.column == self.row_length {
+            let group = self.group << (Self::BITS * (PIXELS - self.pixels));
+
+            if let Some(buf) = self.groups.next() {
+                buf.copy_from_slice(&group.to_be_bytes()[4 - BYTES..]);
+            }
+
+            self.group = 0;
+            self.pixels = 0;
+
+            if self.column == self.row_length {
+                self.column = 0;
+            }
+        }
+    }
+}
+
+/// Fills a buffer storing several pixels per group of bytes, see [`PixelGroupWriter`].
+///
+/// `level` gives the encoded value of a pixel. When the error diffusion is used, the channels
+/// given by `sample` are quantized on the given number of bits instead, then packed with `pack`.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+fn generic_subpixel_fill<
+    const PIXELS: usize,
+    const BYTES: usize,
+    const CHANNELS: usize,
+    S: PixelSource,
+>(
+    source: S,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    level: impl Fn(S::Pixel, &FillOptions, (u32, u32)) -> u32,
+    bits: [u32; CHANNELS],
+    sample: impl Fn(S::Pixel, &FillOptions) -> [u16; CHANNELS],
+    pack: impl Fn([u8; CHANNELS]) -> u32,
+) -> CoreResult<()> {
+    let options = source.fill_options();
+    let mut writer =
+        PixelGroupWriter::<PIXELS, BYTES>::new(target, width, height, options.packing())?;
+
+    if let Dithering::Diffusion { kernel, serpentine } = options.dithering() {
+        return generic_diffused_fill::<CHANNELS, S>(
+            source,
+            x,
+            y,
+            width,
+            height,
+            Diffuser::new(kernel, serpentine, bits, width as usize),
+            |pixel| sample(pixel, &options),
+            |levels| {
+                writer.push(pack(levels));
+
+                Ok(())
+            },
+        );
+    }
+
+    let mut scanlines = source.into_scanlines(x, y, width, height);
+
+    for row_y in (y as u32..).take(height as usize) {
+        let Some(row) = next_full_row(&mut scanlines, width)? else {
+            break;
+        };
+
+        for (row_x, pixel) in (x as u32..).zip(row) {
+            writer.push(level(pixel, &options, (row_x, row_y)));
+        }
     }
 
     Ok(())
 }
 
+/// Fills an indexed buffer with the index of the color of the source palette nearest to each
+/// pixel.
+///
+/// The dithering is not applied to the indexed color modes.
 #[inline]
-fn generic_subpixel_fill<const PIXELS_PER_BYTE: usize, C, I, T>(
-    _iter: T,
-    _target: &mut [u8],
-    _x: u16,
-    _y: u16,
-    _width: u16,
-    _height: u16,
-    _action: fn([C; PIXELS_PER_BYTE], &mut [u8]) -> CoreResult<()>,
-) -> CoreResult<()>
-where
-    C: Color,
-    I: Iterator<Item = C>,
-    T: IntoPixelIter<IntoIter = I, Item = C>,
-{
-    todo!()
+fn generic_indexed_fill<const PIXELS_PER_BYTE: usize, S: PixelSource>(
+    source: S,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    let palette = source.palette().ok_or(CoreError::MissingPalette)?;
+    let bits = PixelGroupWriter::<PIXELS_PER_BYTE, 1>::BITS;
+
+    CoreError::check_palette(palette.len(), 1 << bits)?;
+
+    let mut writer = PixelGroupWriter::<PIXELS_PER_BYTE, 1>::new(
+        target,
+        width,
+        height,
+        source.fill_options().packing(),
+    )?;
+    let mut nearest = NearestColor::new(&palette);
+    let mut scanlines = source.into_scanlines(x, y, width, height);
+
+    for _ in 0..height {
+        let Some(row) = next_full_row(&mut scanlines, width)? else {
+            break;
+        };
+
+        for pixel in row {
+            writer.push(nearest.index(pixel.components().map(Component::to_u8)) as u32);
+        }
+    }
+
+    Ok(())
 }
 
-pub trait Buffer {
-    fn fill_rgb(self, target: &mut [u8], x: u16, y: u16, width: u16, height: u16) -> CoreResult<()>;
-    fn fill_bgr(self, target: &mut [u8], x: u16, y: u16, width: u16, height: u16) -> CoreResult<()>;
+/// Fills a grayscale buffer storing several pixels per byte.
+#[inline]
+fn generic_grayscale_fill<const PIXELS_PER_BYTE: usize, const BITS: usize, S: PixelSource>(
+    source: S,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    generic_subpixel_fill::<PIXELS_PER_BYTE, 1, 1, S>(
+        source,
+        target,
+        x,
+        y,
+        width,
+        height,
+        grayscale_level::<S::Pixel, BITS>,
+        [BITS as u32],
+        |pixel, options| [pixel.luminance_with(options.luminance()).to_u16()],
+        |[level]| level as u32,
+    )
+}
 
-    fn fill_rgb565le(
+/// Converts a source line by line, spreading the quantization error of each pixel with the given
+/// [`Diffuser`].
+///
+/// `sample` gives the channels of a pixel scaled on 16 bits and `emit` receives the levels of the
+/// pixels in the order of the source.
+#[allow(clippy::too_many_arguments)]
+fn generic_diffused_fill<const CHANNELS: usize, S: PixelSource>(
+    source: S,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    mut diffuser: Diffuser<CHANNELS>,
+    sample: impl Fn(S::Pixel) -> [u16; CHANNELS],
+    mut emit: impl FnMut([u8; CHANNELS]) -> CoreResult<()>,
+) -> CoreResult<()> {
+    let mut scanlines = source.into_scanlines(x, y, width, height);
+    let mut line = Vec::with_capacity(width as usize);
+
+    for _ in 0..height {
+        let Some(row) = next_full_row(&mut scanlines, width)? else {
+            break;
+        };
+
+        line.clear();
+        line.extend(row.map(|pixel| sample(pixel).map(i32::from)));
+
+        diffuser.diffuse(&mut line);
+
+        for levels in &line {
+            emit(levels.map(|level| level as u8))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fills a buffer quantizing the components of each pixel on the given number of bits, the
+/// dithering of the source being taken in account.
+///
+/// `encoder` receives the red, green and blue levels of a pixel when the error diffusion is
+/// used, `convertor` is used otherwise.
+#[inline]
+#[allow(clippy::too_many_arguments)]
+fn generic_quantized_fill<const BYTES_PER_PIXEL: usize, S: PixelSource>(
+    source: S,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    convertor: impl Fn(S::Pixel, &FillOptions, (u32, u32), &mut [u8]) -> CoreResult<()>,
+    bits: [u32; 3],
+    encoder: impl Fn([u8; 3], &mut [u8]) -> CoreResult<()>,
+) -> CoreResult<()> {
+    let Dithering::Diffusion { kernel, serpentine } = source.fill_options().dithering() else {
+        return generic_fill::<BYTES_PER_PIXEL, S>(source, target, x, y, width, height, convertor);
+    };
+
+    let mut chunks = target.chunks(
+        BYTES_PER_PIXEL * width as usize,
+        height as usize,
+        BYTES_PER_PIXEL,
+    )?;
+
+    generic_diffused_fill::<3, S>(
+        source,
+        x,
+        y,
+        width,
+        height,
+        Diffuser::new(kernel, serpentine, bits, width as usize),
+        |pixel| pixel.components().map(Component::to_u16),
+        |levels| match chunks.next() {
+            Some(buf) => encoder(levels, buf),
+            None => Ok(()),
+        },
+    )
+}
+
+const fn pack_rgb565([red, green, blue]: [u8; 3]) -> u16 {
+    (red as u16) << 11 | (green as u16) << 5 | blue as u16
+}
+
+const fn pack_bgr565([red, green, blue]: [u8; 3]) -> u16 {
+    (blue as u16) << 11 | (green as u16) << 5 | red as u16
+}
+
+pub trait Buffer: Sized {
+    /// Fills the target with the pixels encoded in the given format, such as a [`ColorMode`].
+    ///
+    /// The length of the target is checked against the storage of the format, see
+    /// [`PixelFormat::storage`]. The indexed color modes use the palette of the source, see
+    /// [`IntoPixelIter::with_palette`].
+    fn fill<F: PixelFormat>(
         self,
+        format: F,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
     ) -> CoreResult<()>;
-    fn fill_rgb565be(
+
+    /// Fills the target as [`Buffer::fill`], converting the large areas in bands of rows on
+    /// several threads.
+    ///
+    /// The output is the one of [`Buffer::fill`], provided the source gives the pixels of the
+    /// area it is asked for since each band is asked separately. See [`PARALLEL_THRESHOLD`] for
+    /// the areas staying converted on the calling thread.
+    #[cfg(feature = "parallel")]
+    fn fill_parallel<F: PixelFormat + Sync>(
         self,
+        format: F,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
+    ) -> CoreResult<()>
+    where
+        Self: Clone + Send;
 
-    fn fill_bgr565le(
+    /// Fills an area of a larger target with the pixels encoded in the given format.
+    ///
+    /// The rows of the area are placed according to the layout, so the target only needs to be
+    /// long enough to hold the last row. Each row begins on a new byte, unless the source uses the
+    /// [`SubpixelPacking::Continuous`] packing: the whole area is then a single row and the stride
+    /// is not used.
+    #[allow(clippy::too_many_arguments)]
+    fn fill_strided<F: PixelFormat>(
         self,
+        format: F,
         target: &mut [u8],
+        layout: TargetLayout,
         x: u16,
         y: u16,
         width: u16,
         height: u16,
     ) -> CoreResult<()>;
-    fn fill_bgr565be(
+
+    // Shorthands of `Buffer::fill` for each color mode
+    fn fill_rgb(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::RGB, target, x, y, width, height)
+    }
 
-    fn fill_grayscale_8bit(
+    fn fill_bgr(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
-    fn fill_grayscale_16bit_le(
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::BGR, target, x, y, width, height)
+    }
+
+    fn fill_xrgb8888(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
-    fn fill_grayscale_16bit_be(
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::XRGB8888, target, x, y, width, height)
+    }
+
+    fn fill_rgba8888(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::RGBA8888, target, x, y, width, height)
+    }
 
-    fn fill_grayscale_1bit(
+    fn fill_rgb666(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
-    fn fill_grayscale_2bit(
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::RGB666, target, x, y, width, height)
+    }
+
+    fn fill_rgb444(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
-    fn fill_grayscale_4bit(
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::RGB444, target, x, y, width, height)
+    }
+
+    fn fill_rgb332(
         self,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
-    ) -> CoreResult<()>;
-}
-
-pub trait IntoPixelIter {
-    type IntoIter: Iterator<Item = Self::Item>;
-    type Item: Color;
-
-    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter;
-}
-
-impl<C, I, T> Buffer for T
-where
-    C: Color,
-    I: Iterator<Item = C>,
-    T: IntoPixelIter<IntoIter = I, Item = C>
-{
-    fn fill_rgb(self, target: &mut [u8], x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
-        generic_fill::<3, C, I, T>(self, target, x, y, width, height, C::fill_rgb)
-    }
-
-    fn fill_bgr(self, target: &mut [u8], x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
-        generic_fill::<3, C, I, T>(self, target, x, y, width, height, C::fill_bgr)
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::RGB332, target, x, y, width, height)
     }
 
     fn fill_rgb565le(
@@ -182,7 +532,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(self, target, x, y, width, height, C::fill_rgb565le)
+        self.fill(ColorMode::RGB565LE, target, x, y, width, height)
     }
 
     fn fill_rgb565be(
@@ -193,7 +543,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(self, target, x, y, width, height, C::fill_rgb565be)
+        self.fill(ColorMode::RGB565BE, target, x, y, width, height)
     }
 
     fn fill_bgr565le(
@@ -204,7 +554,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(self, target, x, y, width, height, C::fill_bgr565le)
+        self.fill(ColorMode::BGR565LE, target, x, y, width, height)
     }
 
     fn fill_bgr565be(
@@ -215,7 +565,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(self, target, x, y, width, height, C::fill_bgr565be)
+        self.fill(ColorMode::BGR565BE, target, x, y, width, height)
     }
 
     fn fill_grayscale_8bit(
@@ -226,7 +576,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<1, C, I, T>(self, target, x, y, width, height, C::fill_grayscale_8bit)
+        self.fill(ColorMode::Grayscale8Bit, target, x, y, width, height)
     }
 
     fn fill_grayscale_16bit_le(
@@ -237,15 +587,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(
-            self,
-            target,
-            x,
-            y,
-            width,
-            height,
-            C::fill_grayscale_16bit_le,
-        )
+        self.fill(ColorMode::Grayscale16BitLE, target, x, y, width, height)
     }
 
     fn fill_grayscale_16bit_be(
@@ -256,15 +598,7 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_fill::<2, C, I, T>(
-            self,
-            target,
-            x,
-            y,
-            width,
-            height,
-            C::fill_grayscale_16bit_be,
-        )
+        self.fill(ColorMode::Grayscale16BitBE, target, x, y, width, height)
     }
 
     fn fill_grayscale_1bit(
@@ -275,52 +609,1373 @@ where
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_subpixel_fill::<8, C, I, T>(
-            self,
+        self.fill(ColorMode::Grayscale1Bit, target, x, y, width, height)
+    }
+
+    fn fill_grayscale_2bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Grayscale2Bit, target, x, y, width, height)
+    }
+
+    fn fill_grayscale_4bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Grayscale4Bit, target, x, y, width, height)
+    }
+
+    fn fill_indexed_1bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Indexed1Bit, target, x, y, width, height)
+    }
+
+    fn fill_indexed_2bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Indexed2Bit, target, x, y, width, height)
+    }
+
+    fn fill_indexed_4bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Indexed4Bit, target, x, y, width, height)
+    }
+
+    fn fill_indexed_8bit(
+        self,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()> {
+        self.fill(ColorMode::Indexed8Bit, target, x, y, width, height)
+    }
+}
+
+pub trait IntoPixelIter {
+    type IntoIter: Iterator<Item = Self::Item>;
+    type Item: Color;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter;
+
+    /// Checks that the source can give every pixel of the area. The fills check the area before
+    /// asking for its pixels, so a source of bounded dimensions rejects the areas going past its
+    /// edges with [`CoreError::AreaOutOfBounds`]. Any area is accepted by default.
+    fn check_area(&self, _x: u16, _y: u16, _width: u16, _height: u16) -> CoreResult<()> {
+        Ok(())
+    }
+
+    /// Gives the options used to encode this source. The default options are used unless the
+    /// source is wrapped with [`IntoPixelIter::with_options`].
+    fn fill_options(&self) -> FillOptions {
+        FillOptions::new()
+    }
+
+    fn with_options(self, options: FillOptions) -> WithOptions<Self>
+    where
+        Self: Sized,
+    {
+        WithOptions::new(self, options)
+    }
+
+    /// Gives the palette used to encode this source in the indexed color modes. There is no
+    /// palette unless the source is wrapped with [`IntoPixelIter::with_palette`].
+    fn palette(&self) -> Option<Palette> {
+        None
+    }
+
+    fn with_palette(self, palette: Palette) -> WithPalette<Self>
+    where
+        Self: Sized,
+    {
+        WithPalette::new(self, palette)
+    }
+
+    /// Gives the rectangle of this source at the given position, see [`Crop`].
+    fn crop(self, x: u16, y: u16, width: u16, height: u16) -> Crop<Self>
+    where
+        Self: Sized,
+    {
+        Crop::new(self, x, y, width, height)
+    }
+
+    /// Mirrors or rotates a frame of this source of the given dimensions, see [`Transformed`].
+    fn transform(self, transform: Transform, width: u16, height: u16) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, transform, width, height)
+    }
+
+    /// Scales a frame of this source of the given dimensions to `width` by `height` pixels, see
+    /// [`Resized`].
+    fn resize(self, source_width: u16, source_height: u16, width: u16, height: u16) -> Resized<Self>
+    where
+        Self: Sized,
+    {
+        Resized::new(self, source_width, source_height, width, height)
+    }
+
+    /// Gives the color mode the source already stores its pixels in, if any.
+    ///
+    /// When an area is encoded in this color mode, its rows given by
+    /// [`IntoPixelIter::native_row`] are copied as is instead of being converted pixel by pixel.
+    /// Only the color modes storing whole bytes per pixel are copied.
+    fn native_mode(&self) -> Option<ColorMode> {
+        None
+    }
+
+    /// Gives the bytes of `width` pixels of the row `y` from the column `x`, encoded in the native
+    /// color mode. A missing row or a row of another length fails the fill with a
+    /// [`CoreError::BufferSizeError`].
+    fn native_row(&self, _x: u16, _y: u16, _width: u16) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Copies the rows of a source storing its pixels in the color mode of the target, which uses
+/// `BYTES_PER_PIXEL` bytes per pixel.
+fn copy_native_rows<S: PixelSource>(
+    source: &S,
+    bytes_per_pixel: usize,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    let buffers = target.rows(bytes_per_pixel * width as usize, height as usize)?;
+
+    for (row, buf) in (0..height).zip(buffers) {
+        let row = y
+            .checked_add(row)
+            .and_then(|row_y| source.native_row(x, row_y, width))
+            .unwrap_or_default();
+
+        CoreError::check_length(row, buf.len())?;
+
+        buf.copy_from_slice(row);
+    }
+
+    Ok(())
+}
+
+/// Fills the target with the pixels of a source encoded in the given color mode.
+fn encode_mode<S: PixelSource>(
+    source: S,
+    mode: ColorMode,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    if source.native_mode() == Some(mode) {
+        if let StorageMode::BytesPerPixel(bytes_per_pixel) = mode.byte_size() {
+            return copy_native_rows(&source, bytes_per_pixel, target, x, y, width, height);
+        }
+    }
+
+    match mode {
+        ColorMode::RGB => {
+            generic_fill::<3, S>(source, target, x, y, width, height, |pixel, _, _, buf| {
+                pixel.fill_rgb(buf)
+            })
+        }
+        ColorMode::BGR => {
+            generic_fill::<3, S>(source, target, x, y, width, height, |pixel, _, _, buf| {
+                pixel.fill_bgr(buf)
+            })
+        }
+        ColorMode::XRGB8888 => {
+            generic_fill::<4, S>(source, target, x, y, width, height, |pixel, _, _, buf| {
+                pixel.fill_xrgb8888(buf)
+            })
+        }
+        ColorMode::RGBA8888 => {
+            generic_fill::<4, S>(source, target, x, y, width, height, |pixel, _, _, buf| {
+                pixel.fill_rgba8888(buf)
+            })
+        }
+        ColorMode::RGB666 => generic_quantized_fill::<3, S>(
+            source,
             target,
             x,
             y,
             width,
             height,
-            C::fill_grayscale_1bit,
-        )
+            |pixel, options, (x, y), buf| pixel.fill_rgb666_with(options.quantizer_at(x, y), buf),
+            [6, 6, 6],
+            |levels, buf| {
+                buf.copy_from_slice(&levels.map(|level| level << 2));
+
+                Ok(())
+            },
+        ),
+        ColorMode::RGB444 => generic_subpixel_fill::<2, 3, 3, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y)| pixel.into_rgb444_with(options.quantizer_at(x, y)) as u32,
+            [4, 4, 4],
+            |pixel, _| pixel.components().map(Component::to_u16),
+            |[red, green, blue]| (red as u32) << 8 | (green as u32) << 4 | blue as u32,
+        ),
+        ColorMode::RGB332 => generic_quantized_fill::<1, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y), buf| pixel.fill_rgb332_with(options.quantizer_at(x, y), buf),
+            [3, 3, 2],
+            |[red, green, blue], buf| {
+                buf[0] = red << 5 | green << 2 | blue;
+
+                Ok(())
+            },
+        ),
+        ColorMode::RGB565LE => generic_quantized_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y), buf| pixel.fill_rgb565le_with(options.quantizer_at(x, y), buf),
+            [5, 6, 5],
+            |levels, buf| pack_rgb565(levels).convert_le(buf),
+        ),
+        ColorMode::RGB565BE => generic_quantized_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y), buf| pixel.fill_rgb565be_with(options.quantizer_at(x, y), buf),
+            [5, 6, 5],
+            |levels, buf| pack_rgb565(levels).convert_be(buf),
+        ),
+        ColorMode::BGR565LE => generic_quantized_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y), buf| pixel.fill_bgr565le_with(options.quantizer_at(x, y), buf),
+            [5, 6, 5],
+            |levels, buf| pack_bgr565(levels).convert_le(buf),
+        ),
+        ColorMode::BGR565BE => generic_quantized_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, (x, y), buf| pixel.fill_bgr565be_with(options.quantizer_at(x, y), buf),
+            [5, 6, 5],
+            |levels, buf| pack_bgr565(levels).convert_be(buf),
+        ),
+        ColorMode::Grayscale8Bit => generic_fill::<1, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, _, buf| pixel.fill_grayscale_8bit_with(options.luminance(), buf),
+        ),
+        ColorMode::Grayscale16BitLE => generic_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, _, buf| pixel.fill_grayscale_16bit_le_with(options.luminance(), buf),
+        ),
+        ColorMode::Grayscale16BitBE => generic_fill::<2, S>(
+            source,
+            target,
+            x,
+            y,
+            width,
+            height,
+            |pixel, options, _, buf| pixel.fill_grayscale_16bit_be_with(options.luminance(), buf),
+        ),
+        ColorMode::Grayscale1Bit => {
+            generic_grayscale_fill::<8, 1, S>(source, target, x, y, width, height)
+        }
+        ColorMode::Grayscale2Bit => {
+            generic_grayscale_fill::<4, 2, S>(source, target, x, y, width, height)
+        }
+        ColorMode::Grayscale4Bit => {
+            generic_grayscale_fill::<2, 4, S>(source, target, x, y, width, height)
+        }
+        ColorMode::Indexed1Bit => generic_indexed_fill::<8, S>(source, target, x, y, width, height),
+        ColorMode::Indexed2Bit => generic_indexed_fill::<4, S>(source, target, x, y, width, height),
+        ColorMode::Indexed4Bit => generic_indexed_fill::<2, S>(source, target, x, y, width, height),
+        ColorMode::Indexed8Bit => generic_indexed_fill::<1, S>(source, target, x, y, width, height),
     }
+}
 
-    fn fill_grayscale_2bit(
+impl<S: PixelSource> Buffer for S {
+    fn fill<F: PixelFormat>(
         self,
+        format: F,
         target: &mut [u8],
         x: u16,
         y: u16,
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_subpixel_fill::<4, C, I, T>(
+        self.check_area(x, y, width, height)?;
+
+        let storage = format.storage();
+        let packing = self.fill_options().packing();
+
+        CoreError::check_storage(storage)?;
+        CoreError::check_length(target, storage.buffer_length(width, height, packing))?;
+
+        format.encode(self, FillTarget::packed(target), x, y, width, height)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn fill_parallel<F: PixelFormat + Sync>(
+        self,
+        format: F,
+        target: &mut [u8],
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> CoreResult<()>
+    where
+        Self: Clone + Send,
+    {
+        self.check_area(x, y, width, height)?;
+
+        let storage = format.storage();
+        let packing = self.fill_options().packing();
+
+        CoreError::check_storage(storage)?;
+        CoreError::check_length(target, storage.buffer_length(width, height, packing))?;
+
+        parallel::encode_parallel(
             self,
-            target,
+            &format,
+            FillTarget::packed(target),
             x,
             y,
             width,
             height,
-            C::fill_grayscale_2bit,
         )
     }
 
-    fn fill_grayscale_4bit(
+    fn fill_strided<F: PixelFormat>(
         self,
+        format: F,
         target: &mut [u8],
+        layout: TargetLayout,
         x: u16,
         y: u16,
         width: u16,
         height: u16,
     ) -> CoreResult<()> {
-        generic_subpixel_fill::<2, C, I, T>(
+        self.check_area(x, y, width, height)?;
+
+        format.encode(
             self,
-            target,
+            FillTarget::with_layout(target, layout),
             x,
             y,
             width,
             height,
-            C::fill_grayscale_4bit,
         )
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use crate::test_helper::{DummyBuffer, COLOR_BLUE};
+    use crate::{
+        Buffer, Color, ColorMode, CoreError, CoreResult, DiffusionKernel, Dithering, FillOptions,
+        IntoPixelIter, Luminance, Palette, PixelSource, Quantizer, Scanlines, SubpixelPacking,
+        TargetLayout,
+    };
+
+    #[derive(Clone, Copy)]
+    struct Gray(u8);
+
+    struct Pixels<'a>(&'a [u8]);
+
+    impl Color for Gray {
+        type Component = u8;
+
+        fn components(self) -> [u8; 3] {
+            [self.0, self.0, self.0]
+        }
+    }
+
+    impl<'a> IntoPixelIter for Pixels<'a> {
+        type IntoIter = std::iter::Map<std::slice::Iter<'a, u8>, fn(&u8) -> Gray>;
+        type Item = Gray;
+
+        // Ends early when the area is larger than the image
+        fn into_pixel_iter(self, _x: u16, _y: u16, width: u16, height: u16) -> Self::IntoIter {
+            let length = (width as usize * height as usize).min(self.0.len());
+
+            self.0[..length].iter().map(|value| Gray(*value))
+        }
+    }
+
+    const CONTINUOUS: FillOptions = FillOptions::new().with_packing(SubpixelPacking::Continuous);
+
+    // 5x2 image, each value hitting a different level in the 1, 2 and 4 bits modes
+    const IMAGE: [u8; 10] = [0xFF, 0x00, 0x80, 0x40, 0xC0, 0x10, 0xF0, 0x7F, 0x20, 0xA0];
+
+    #[test]
+    fn test_fill_grayscale_1bit() {
+        let mut buffer = [0xAAu8; 2];
+
+        Pixels(&IMAGE)
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b10101_000, 0b01001_000]);
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b10101_010, 0b01_000000]);
+
+        let mut buffer = [0u8; 1];
+
+        Pixels(&IMAGE)
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 8, 1)
+            .unwrap();
+        assert_eq!(buffer, [0b10101010]);
+    }
+
+    #[test]
+    fn test_fill_grayscale_2bit() {
+        let mut buffer = [0xAAu8; 4];
+
+        Pixels(&IMAGE)
+            .fill_grayscale_2bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [0b11_00_10_01, 0b10_000000, 0b00_11_01_00, 0b10_000000]
+        );
+
+        let mut buffer = [0xAAu8; 3];
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill_grayscale_2bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b11_00_10_01, 0b10_00_11_01, 0b00_10_0000]);
+    }
+
+    #[test]
+    fn test_fill_grayscale_4bit() {
+        let mut buffer = [0xAAu8; 6];
+
+        Pixels(&IMAGE)
+            .fill_grayscale_4bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [0xF0, 0x84, 0xB0, 0x1E, 0x72, 0x90]);
+
+        let mut buffer = [0xAAu8; 5];
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill_grayscale_4bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [0xF0, 0x84, 0xB1, 0xE7, 0x29]);
+    }
+
+    #[test]
+    fn test_fill_subpixel_length() {
+        let mut buffer = [0u8; 2];
+
+        assert_eq!(
+            Pixels(&IMAGE).fill_grayscale_2bit(&mut buffer, 0, 0, 5, 2),
+            Err(CoreError::BufferSizeError {
+                given: 2,
+                expected: 4
+            })
+        );
+        assert_eq!(
+            Pixels(&IMAGE)
+                .with_options(CONTINUOUS)
+                .fill_grayscale_4bit(&mut buffer, 0, 0, 5, 2),
+            Err(CoreError::BufferSizeError {
+                given: 2,
+                expected: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_grayscale_luminance() {
+        let blue = DummyBuffer::new(COLOR_BLUE);
+        let rec709 = FillOptions::new().with_luminance(Luminance::Rec709);
+        let mut buffer = [0u8; 4];
+
+        blue.fill_grayscale_8bit(&mut buffer, 0, 0, 2, 2).unwrap();
+        assert_eq!(buffer, [0xFF; 4]);
+
+        blue.with_options(rec709)
+            .fill_grayscale_8bit(&mut buffer, 0, 0, 2, 2)
+            .unwrap();
+        assert_eq!(buffer, [18; 4]);
+
+        blue.with_options(rec709)
+            .fill_grayscale_16bit_le(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [18; 4]);
+
+        blue.with_options(rec709.with_luminance(Luminance::Average))
+            .fill_grayscale_4bit(&mut buffer[..1], 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer[0], 0x55);
+    }
+
+    #[test]
+    fn test_fill_565_quantizer() {
+        let clamp = FillOptions::new().with_quantizer(Quantizer::RoundWithClamp);
+        let mut buffer = [0u8; 4];
+
+        Pixels(&[0x7C; 2])
+            .fill_rgb565le(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer[..2], 0b01111_011111_01111u16.to_le_bytes());
+
+        Pixels(&[0x7C; 2])
+            .with_options(clamp)
+            .fill_rgb565le(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer[..2], 0b10000_011111_10000u16.to_le_bytes());
+        assert_eq!(buffer[2..], 0b10000_011111_10000u16.to_le_bytes());
+
+        Pixels(&[0x7C; 2])
+            .with_options(clamp)
+            .fill_bgr565be(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer[..2], 0b10000_011111_10000u16.to_be_bytes());
+    }
+
+    #[test]
+    fn test_fill_grayscale_ordered_dithering() {
+        let bayer = FillOptions::new().with_dithering(Dithering::Bayer2x2);
+        let mut buffer = [0u8; 2];
+
+        Pixels(&[0x80; 8])
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 4, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b1111_0000, 0b1111_0000]);
+
+        Pixels(&[0x80; 8])
+            .with_options(bayer)
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 4, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b0101_0000, 0b1010_0000]);
+
+        // The pattern follows the absolute position of the pixels
+        Pixels(&[0x80; 8])
+            .with_options(bayer)
+            .fill_grayscale_1bit(&mut buffer, 1, 0, 4, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b1010_0000, 0b0101_0000]);
+
+        Pixels(&[0x80; 8])
+            .with_options(bayer)
+            .fill_grayscale_1bit(&mut buffer, 2, 1, 4, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b1010_0000, 0b0101_0000]);
+
+        // 0x6C is 1.27 steps of 2 bits, the second row of the matrix adds 0.78, 0.28, 0.91 and 0.41
+        Pixels(&[0x6C; 4])
+            .with_options(FillOptions::new().with_dithering(Dithering::Bayer4x4))
+            .fill_grayscale_2bit(&mut buffer[..1], 0, 1, 4, 1)
+            .unwrap();
+        assert_eq!(buffer[0], 0b10_01_10_01);
+    }
+
+    #[test]
+    fn test_fill_565_ordered_dithering() {
+        const LOW: u16 = 0b01111_011111_01111;
+        const HIGH: u16 = 0b10000_100000_10000;
+
+        let bayer = FillOptions::new().with_dithering(Dithering::Bayer2x2);
+        let mut buffer = [0u8; 8];
+
+        Pixels(&[0x80; 4])
+            .with_options(bayer)
+            .fill_rgb565be(&mut buffer, 0, 0, 2, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [LOW, HIGH, HIGH, LOW]
+                .map(u16::to_be_bytes)
+                .concat()
+                .as_slice()
+        );
+
+        Pixels(&[0x80; 4])
+            .with_options(bayer.with_quantizer(Quantizer::RoundWithClamp))
+            .fill_bgr565le(&mut buffer, 1, 0, 2, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [HIGH, LOW, LOW, HIGH]
+                .map(u16::to_le_bytes)
+                .concat()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_fill_grayscale_error_diffusion() {
+        let floyd_steinberg = FillOptions::new().with_dithering(Dithering::Diffusion {
+            kernel: DiffusionKernel::FloydSteinberg,
+            serpentine: false,
+        });
+        let mut buffer = [0u8; 2];
+
+        Pixels(&[0x80; 8])
+            .with_options(floyd_steinberg)
+            .fill_grayscale_1bit(&mut buffer, 0, 0, 4, 2)
+            .unwrap();
+        assert_eq!(buffer, [0b1010_0000, 0b0101_0000]);
+
+        // The error diffusion does not depend on the position of the area
+        Pixels(&[0x80; 8])
+            .with_options(floyd_steinberg.with_packing(SubpixelPacking::Continuous))
+            .fill_grayscale_1bit(&mut buffer[..1], 3, 5, 4, 2)
+            .unwrap();
+        assert_eq!(buffer[0], 0b1010_0101);
+
+        Pixels(&IMAGE)
+            .with_options(floyd_steinberg)
+            .fill_grayscale_4bit(&mut buffer[..1], 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer[0], 0xF0);
+    }
+
+    #[test]
+    fn test_fill_565_error_diffusion() {
+        let atkinson = FillOptions::new().with_dithering(Dithering::Diffusion {
+            kernel: DiffusionKernel::Atkinson,
+            serpentine: true,
+        });
+        let mut buffer = [0u8; 32];
+
+        Pixels(&[0xFF; 16])
+            .with_options(atkinson)
+            .fill_rgb565le(&mut buffer, 0, 0, 4, 4)
+            .unwrap();
+        assert_eq!(buffer, [0xFF; 32]);
+
+        // 0x80 is 15.56 steps of 5 bits and 31.62 steps of 6 bits
+        Pixels(&[0x80; 16])
+            .with_options(atkinson)
+            .fill_bgr565be(&mut buffer, 0, 0, 4, 4)
+            .unwrap();
+
+        let levels = buffer
+            .chunks(2)
+            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+            .map(|pixel| [pixel & 0x1F, pixel >> 5 & 0x3F, pixel >> 11])
+            .collect::<Vec<_>>();
+
+        for &[red, green, blue] in levels.iter() {
+            assert_eq!(red, blue);
+            assert!((15..=16).contains(&red));
+            assert!((31..=32).contains(&green));
+        }
+
+        assert!(levels.iter().any(|[red, _, _]| *red == 15));
+        assert!(levels.iter().any(|[red, _, _]| *red == 16));
+
+        assert_eq!(
+            Pixels(&[0x80; 16])
+                .with_options(atkinson)
+                .fill_rgb565be(&mut buffer[..30], 0, 0, 4, 4),
+            Err(CoreError::BufferSizeError {
+                given: 30,
+                expected: 32
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_32bit() {
+        let mut buffer = [0u8; 8];
+
+        Pixels(&IMAGE[..2])
+            .fill_xrgb8888(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00]);
+
+        Pixels(&IMAGE[2..])
+            .fill_rgba8888(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [0x80, 0x80, 0x80, 0xFF, 0x40, 0x40, 0x40, 0xFF]);
+    }
+
+    #[test]
+    fn test_fill_rgb666() {
+        let mut buffer = [0u8; 6];
+
+        Pixels(&IMAGE[3..])
+            .fill_rgb666(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [0x40, 0x40, 0x40, 0xC0, 0xC0, 0xC0]);
+    }
+
+    #[test]
+    fn test_fill_rgb444() {
+        let mut buffer = [0xAAu8; 18];
+
+        Pixels(&IMAGE).fill_rgb444(&mut buffer, 0, 0, 5, 2).unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0xFF, 0xF0, 0x00, 0x88, 0x84, 0x44, 0xCC, 0xC0, 0x00, // First row
+                0x11, 0x1F, 0xFF, 0x77, 0x72, 0x22, 0xAA, 0xA0, 0x00, // Second row
+            ]
+        );
+
+        let mut buffer = [0xAAu8; 15];
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill_rgb444(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0xFF, 0xF0, 0x00, 0x88, 0x84, 0x44, 0xCC, 0xC1, 0x11, 0xFF, 0xF7, 0x77, 0x22, 0x2A,
+                0xAA,
+            ]
+        );
+
+        assert_eq!(
+            Pixels(&IMAGE).fill_rgb444(&mut buffer, 0, 0, 5, 2),
+            Err(CoreError::BufferSizeError {
+                given: 15,
+                expected: 18
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_rgb332() {
+        let mut buffer = [0u8; 5];
+
+        Pixels(&IMAGE[..5])
+            .fill_rgb332(&mut buffer, 0, 0, 5, 1)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0b111_111_11,
+                0b000_000_00,
+                0b100_100_10,
+                0b010_010_01,
+                0b110_110_11
+            ]
+        );
+
+        // 0x40 is 0.75 steps of 2 bits
+        let mut buffer = [0u8; 64];
+
+        Pixels(&[0x40; 64])
+            .with_options(FillOptions::new().with_dithering(Dithering::Diffusion {
+                kernel: DiffusionKernel::FloydSteinberg,
+                serpentine: false,
+            }))
+            .fill_rgb332(&mut buffer, 0, 0, 8, 8)
+            .unwrap();
+
+        let blue = buffer
+            .iter()
+            .map(|pixel| (pixel & 0b11) as u32)
+            .sum::<u32>();
+
+        assert!(buffer.iter().all(|pixel| pixel & 0b11 <= 1));
+        assert!((44..=52).contains(&blue));
+    }
+
+    #[test]
+    fn test_fill_indexed() {
+        let grays = Palette::new(&[[0x00; 3], [0x55; 3], [0xAA; 3], [0xFF; 3]]).unwrap();
+        let mut buffer = [0xAAu8; 4];
+
+        Pixels(&IMAGE)
+            .with_palette(grays.clone())
+            .fill_indexed_2bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [0b11_00_10_01, 0b10_000000, 0b00_11_01_00, 0b10_000000]
+        );
+
+        Pixels(&IMAGE)
+            .with_palette(grays.clone())
+            .with_options(CONTINUOUS)
+            .fill(ColorMode::Indexed2Bit, &mut buffer[..3], 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer[..3], [0b11_00_10_01, 0b10_00_11_01, 0b00_10_0000]);
+
+        let mut buffer = [0u8; 10];
+
+        Pixels(&IMAGE)
+            .with_palette(grays.clone())
+            .fill_indexed_8bit(&mut buffer, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(buffer, [3, 0, 2, 1, 2, 0, 3, 1, 0, 2]);
+
+        assert_eq!(
+            Pixels(&IMAGE).fill_indexed_8bit(&mut buffer, 0, 0, 5, 2),
+            Err(CoreError::MissingPalette)
+        );
+        assert_eq!(
+            Pixels(&IMAGE)
+                .with_palette(grays)
+                .fill_indexed_1bit(&mut buffer[..2], 0, 0, 5, 2),
+            Err(CoreError::InvalidPalette {
+                given: 4,
+                maximum: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_strided() {
+        // 5x2 area at (1, 1) of a 7x3 staging buffer
+        let layout = TargetLayout::new(7).with_offset(8);
+        let mut buffer = [0x55u8; 21];
+
+        Pixels(&IMAGE)
+            .fill_strided(ColorMode::Grayscale8Bit, &mut buffer, layout, 0, 0, 5, 2)
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0x55, 0x55, 0x55, 0x55, 0x55, 0x55, 0x55, // First row
+                0x55, 0xFF, 0x00, 0x80, 0x40, 0xC0, 0x55, // Second row
+                0x55, 0x10, 0xF0, 0x7F, 0x20, 0xA0, 0x55, // Third row
+            ]
+        );
+
+        // The last row does not need a full stride
+        let mut buffer = [0x55u8; 12];
+
+        Pixels(&IMAGE)
+            .fill_strided(
+                ColorMode::RGB565BE,
+                &mut buffer,
+                TargetLayout::new(8),
+                0,
+                0,
+                2,
+                2,
+            )
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [0xFF, 0xFF, 0x00, 0x00, 0x55, 0x55, 0x55, 0x55, 0x84, 0x10, 0x42, 0x08]
+        );
+
+        let mut buffer = [0x55u8; 6];
+
+        Pixels(&IMAGE)
+            .fill_strided(
+                ColorMode::Grayscale2Bit,
+                &mut buffer,
+                TargetLayout::new(3).with_offset(1),
+                0,
+                0,
+                5,
+                2,
+            )
+            .unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0x55,
+                0b11_00_10_01,
+                0b10_000000,
+                0x55,
+                0b00_11_01_00,
+                0b10_000000
+            ]
+        );
+
+        // Continuously packed areas are a single row
+        let mut buffer = [0x55u8; 4];
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill_strided(
+                ColorMode::Grayscale2Bit,
+                &mut buffer,
+                TargetLayout::new(1).with_offset(1),
+                0,
+                0,
+                5,
+                2,
+            )
+            .unwrap();
+        assert_eq!(buffer, [0x55, 0b11_00_10_01, 0b10_00_11_01, 0b00_10_0000]);
+
+        // The diffusion follows the layout too
+        let mut expected = [0u8; 8];
+        let mut buffer = [0x55u8; 10];
+        let atkinson = FillOptions::new().with_dithering(Dithering::Diffusion {
+            kernel: DiffusionKernel::Atkinson,
+            serpentine: false,
+        });
+
+        Pixels(&IMAGE)
+            .with_options(atkinson)
+            .fill_rgb565le(&mut expected, 0, 0, 2, 2)
+            .unwrap();
+        Pixels(&IMAGE)
+            .with_options(atkinson)
+            .fill_strided(
+                ColorMode::RGB565LE,
+                &mut buffer,
+                TargetLayout::new(5).with_offset(1),
+                0,
+                0,
+                2,
+                2,
+            )
+            .unwrap();
+        assert_eq!(buffer[1..5], expected[..4]);
+        assert_eq!(buffer[6..], expected[4..]);
+        assert_eq!([buffer[0], buffer[5]], [0x55, 0x55]);
+    }
+
+    #[test]
+    fn test_fill_strided_length() {
+        let mut buffer = [0u8; 20];
+
+        assert_eq!(
+            Pixels(&IMAGE).fill_strided(
+                ColorMode::RGB,
+                &mut buffer,
+                TargetLayout::new(16).with_offset(2),
+                0,
+                0,
+                2,
+                2
+            ),
+            Err(CoreError::BufferSizeError {
+                given: 20,
+                expected: 24
+            })
+        );
+        assert_eq!(
+            Pixels(&IMAGE).fill_strided(
+                ColorMode::RGB,
+                &mut buffer,
+                TargetLayout::new(5),
+                0,
+                0,
+                2,
+                2
+            ),
+            Err(CoreError::InvalidStride {
+                stride: 5,
+                row_length: 6
+            })
+        );
+        assert_eq!(
+            Pixels(&IMAGE).fill_strided(
+                ColorMode::Grayscale1Bit,
+                &mut buffer,
+                TargetLayout::new(1).with_offset(18),
+                0,
+                0,
+                5,
+                2
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_fill_mode() {
+        type Fill = fn(Pixels<'static>, &mut [u8], u16, u16, u16, u16) -> CoreResult<()>;
+
+        let modes: [(ColorMode, Fill); 17] = [
+            (ColorMode::RGB, Pixels::fill_rgb),
+            (ColorMode::BGR, Pixels::fill_bgr),
+            (ColorMode::XRGB8888, Pixels::fill_xrgb8888),
+            (ColorMode::RGBA8888, Pixels::fill_rgba8888),
+            (ColorMode::RGB666, Pixels::fill_rgb666),
+            (ColorMode::RGB444, Pixels::fill_rgb444),
+            (ColorMode::RGB332, Pixels::fill_rgb332),
+            (ColorMode::RGB565LE, Pixels::fill_rgb565le),
+            (ColorMode::RGB565BE, Pixels::fill_rgb565be),
+            (ColorMode::BGR565LE, Pixels::fill_bgr565le),
+            (ColorMode::BGR565BE, Pixels::fill_bgr565be),
+            (ColorMode::Grayscale1Bit, Pixels::fill_grayscale_1bit),
+            (ColorMode::Grayscale2Bit, Pixels::fill_grayscale_2bit),
+            (ColorMode::Grayscale4Bit, Pixels::fill_grayscale_4bit),
+            (ColorMode::Grayscale8Bit, Pixels::fill_grayscale_8bit),
+            (ColorMode::Grayscale16BitLE, Pixels::fill_grayscale_16bit_le),
+            (ColorMode::Grayscale16BitBE, Pixels::fill_grayscale_16bit_be),
+        ];
+
+        for (mode, fill) in modes {
+            let length = mode
+                .byte_size()
+                .buffer_length(5, 2, SubpixelPacking::RowPadded);
+            let mut expected = vec![0u8; length];
+            let mut buffer = vec![0u8; length];
+
+            fill(Pixels(&IMAGE), &mut expected, 0, 0, 5, 2).unwrap();
+            Pixels(&IMAGE).fill(mode, &mut buffer, 0, 0, 5, 2).unwrap();
+
+            assert_eq!(buffer, expected, "{mode:?}");
+
+            let mut buffer = vec![0u8; length + 1];
+
+            assert_eq!(
+                Pixels(&IMAGE).fill(mode, &mut buffer, 0, 0, 5, 2),
+                Err(CoreError::BufferSizeError {
+                    given: length + 1,
+                    expected: length
+                }),
+                "{mode:?}"
+            );
+        }
+
+        // The packing of the source is used to compute the length
+        let mut buffer = [0u8; 2];
+
+        Pixels(&IMAGE)
+            .with_options(CONTINUOUS)
+            .fill(ColorMode::Grayscale1Bit, &mut buffer, 0, 0, 5, 2)
+            .unwrap();
+
+        assert_eq!(buffer, [0b10101_010, 0b01_000000]);
+    }
+
+    /// Source lending the rows of an image stored in memory. The rows miss their last pixel when
+    /// `truncated`.
+    struct GrayImage<'a> {
+        pixels: &'a [Gray],
+        options: FillOptions,
+        truncated: bool,
+    }
+
+    struct GrayRows<'a> {
+        rows: std::slice::ChunksExact<'a, Gray>,
+        left: usize,
+        right: usize,
+    }
+
+    impl<'a> PixelSource for GrayImage<'a> {
+        type Scanlines = GrayRows<'a>;
+        type Pixel = Gray;
+
+        fn into_scanlines(self, x: u16, y: u16, width: u16, height: u16) -> GrayRows<'a> {
+            GrayRows {
+                rows: self.pixels[5 * y as usize..5 * (y + height) as usize].chunks_exact(5),
+                left: x as usize,
+                right: (x + width) as usize - self.truncated as usize,
+            }
+        }
+
+        fn fill_options(&self) -> FillOptions {
+            self.options
+        }
+    }
+
+    impl<'a> Scanlines for GrayRows<'a> {
+        type Pixel = Gray;
+
+        type Row<'b>
+            = std::iter::Copied<std::slice::Iter<'b, Gray>>
+        where
+            Self: 'b;
+
+        fn next_row(&mut self) -> CoreResult<Option<Self::Row<'_>>> {
+            Ok(self
+                .rows
+                .next()
+                .map(|row| row[self.left..self.right].iter().copied()))
+        }
+    }
+
+    #[test]
+    fn test_fill_scanlines() {
+        let pixels = IMAGE.map(Gray);
+        let options = [
+            FillOptions::new(),
+            CONTINUOUS,
+            FillOptions::new().with_dithering(Dithering::Bayer4x4),
+            FillOptions::new().with_dithering(Dithering::Diffusion {
+                kernel: DiffusionKernel::FloydSteinberg,
+                serpentine: true,
+            }),
+        ];
+
+        for options in options {
+            for mode in [
+                ColorMode::RGB,
+                ColorMode::XRGB8888,
+                ColorMode::RGB666,
+                ColorMode::RGB444,
+                ColorMode::RGB332,
+                ColorMode::RGB565LE,
+                ColorMode::BGR565BE,
+                ColorMode::Grayscale1Bit,
+                ColorMode::Grayscale2Bit,
+                ColorMode::Grayscale4Bit,
+                ColorMode::Grayscale8Bit,
+                ColorMode::Grayscale16BitLE,
+            ] {
+                let length = mode.byte_size().buffer_length(5, 2, options.packing());
+                let mut expected = vec![0u8; length];
+                let mut buffer = vec![0u8; length];
+
+                Pixels(&IMAGE)
+                    .with_options(options)
+                    .fill(mode, &mut expected, 0, 0, 5, 2)
+                    .unwrap();
+                GrayImage {
+                    pixels: &pixels,
+                    options,
+                    truncated: false,
+                }
+                .fill(mode, &mut buffer, 0, 0, 5, 2)
+                .unwrap();
+
+                assert_eq!(buffer, expected, "{mode:?} {options:?}");
+            }
+        }
+
+        // Only the rows of the area are lent
+        let mut buffer = [0u8; 3];
+
+        GrayImage {
+            pixels: &pixels,
+            options: FillOptions::new(),
+            truncated: false,
+        }
+        .fill_grayscale_8bit(&mut buffer, 1, 1, 3, 1)
+        .unwrap();
+
+        assert_eq!(buffer, [0xF0, 0x7F, 0x20]);
+    }
+
+    #[test]
+    fn test_fill_missing_pixels() {
+        let pixels = IMAGE.map(Gray);
+        let options = [
+            FillOptions::new(),
+            CONTINUOUS,
+            FillOptions::new().with_dithering(Dithering::Diffusion {
+                kernel: DiffusionKernel::FloydSteinberg,
+                serpentine: false,
+            }),
+        ];
+
+        for options in options {
+            for mode in [
+                ColorMode::RGB,
+                ColorMode::RGB565LE,
+                ColorMode::Grayscale1Bit,
+                ColorMode::Grayscale8Bit,
+            ] {
+                let length = mode.byte_size().buffer_length(5, 3, options.packing());
+                let mut buffer = vec![0u8; length];
+
+                // The image ends after the second row
+                assert_eq!(
+                    Pixels(&IMAGE)
+                        .with_options(options)
+                        .fill(mode, &mut buffer, 0, 0, 5, 3),
+                    Err(CoreError::BufferSizeError {
+                        given: 0,
+                        expected: 5
+                    }),
+                    "{mode:?} {options:?}"
+                );
+
+                // And in the middle of the third row of 4 pixels
+                let length = mode.byte_size().buffer_length(4, 3, options.packing());
+
+                assert_eq!(
+                    Pixels(&IMAGE).with_options(options).fill(
+                        mode,
+                        &mut vec![0u8; length],
+                        0,
+                        0,
+                        4,
+                        3
+                    ),
+                    Err(CoreError::BufferSizeError {
+                        given: 2,
+                        expected: 4
+                    }),
+                    "{mode:?} {options:?}"
+                );
+
+                // The rows lent are too short
+                let source = GrayImage {
+                    pixels: &pixels,
+                    options,
+                    truncated: true,
+                };
+
+                let length = mode.byte_size().buffer_length(5, 2, options.packing());
+
+                assert_eq!(
+                    source.fill(mode, &mut vec![0u8; length], 0, 0, 5, 2),
+                    Err(CoreError::BufferSizeError {
+                        given: 4,
+                        expected: 5
+                    }),
+                    "{mode:?} {options:?}"
+                );
+            }
+        }
+    }
+
+    /// Source of the 5x2 image whose native bytes are inverted, so a copy is told apart from a
+    /// conversion. The native rows miss their last byte when `truncated`.
+    struct Native {
+        inverted: [u8; 10],
+        truncated: bool,
+    }
+
+    impl<'a> IntoPixelIter for &'a Native {
+        type IntoIter = std::iter::Map<std::slice::Iter<'a, u8>, fn(&u8) -> Gray>;
+        type Item = Gray;
+
+        fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+            Pixels(&IMAGE).into_pixel_iter(x, y, width, height)
+        }
+
+        fn native_mode(&self) -> Option<ColorMode> {
+            Some(ColorMode::Grayscale8Bit)
+        }
+
+        fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+            self.inverted
+                .get(5 * y as usize..5 * (y as usize + 1))
+                .map(|row| &row[x as usize..(x + width) as usize - self.truncated as usize])
+        }
+    }
+
+    #[test]
+    fn test_fill_native() {
+        let native = Native {
+            inverted: IMAGE.map(|level| !level),
+            truncated: false,
+        };
+
+        let mut buffer = [0u8; 6];
+
+        native.fill_grayscale_8bit(&mut buffer, 1, 0, 3, 2).unwrap();
+        assert_eq!(buffer, [0xFF, 0x7F, 0xBF, 0x0F, 0x80, 0xDF]);
+
+        // The options do not apply to the native rows
+        native
+            .with_options(FillOptions::new().with_luminance(Luminance::Average))
+            .fill(ColorMode::Grayscale8Bit, &mut buffer, 1, 0, 3, 2)
+            .unwrap();
+        assert_eq!(buffer, [0xFF, 0x7F, 0xBF, 0x0F, 0x80, 0xDF]);
+
+        let mut buffer = [0u8; 11];
+        let layout = TargetLayout::new(4).with_offset(1);
+
+        native
+            .fill_strided(ColorMode::Grayscale8Bit, &mut buffer, layout, 0, 0, 2, 2)
+            .unwrap();
+        assert_eq!(buffer, [0, 0x00, 0xFF, 0, 0, 0xEF, 0x0F, 0, 0, 0, 0]);
+
+        // The source ends after the second row
+        assert_eq!(
+            native.fill_strided(ColorMode::Grayscale8Bit, &mut buffer, layout, 0, 0, 2, 3),
+            Err(CoreError::BufferSizeError {
+                given: 0,
+                expected: 2
+            })
+        );
+
+        // The other color modes are converted
+        let mut buffer = [0u8; 4];
+
+        native
+            .fill_grayscale_16bit_be(&mut buffer, 0, 0, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [0xFF, 0xFF, 0x00, 0x00]);
+
+        let mut buffer = [0u8; 2];
+
+        native.fill_grayscale_1bit(&mut buffer, 0, 0, 5, 2).unwrap();
+        assert_eq!(buffer, [0b10101_000, 0b01001_000]);
+
+        // A native row too short is not copied partly
+        let native = Native {
+            truncated: true,
+            ..native
+        };
+        let mut buffer = [0xEEu8; 3];
+
+        assert_eq!(
+            native.fill_grayscale_8bit(&mut buffer, 1, 0, 3, 1),
+            Err(CoreError::BufferSizeError {
+                given: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(buffer, [0xEE; 3]);
+    }
+}
@@ -0,0 +1,122 @@
+use super::{ColorMode, Dithering, IntoPixelIter, Luminance, Palette, Quantizer, SubpixelPacking};
+use crate::CoreResult;
+
+/// Settings used by the [`Buffer`](super::Buffer) methods to encode a pixel source.
+///
+/// The options are carried by the source itself (see [`IntoPixelIter::fill_options`]) so they
+/// reach the encoder even when the source is handed to a driver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FillOptions {
+    packing: SubpixelPacking,
+    luminance: Luminance,
+    quantizer: Quantizer,
+    dithering: Dithering,
+}
+
+/// Pixel source overriding the [`FillOptions`] of another source.
+///
+/// Built by [`IntoPixelIter::with_options`].
+#[derive(Clone, Copy, Debug)]
+pub struct WithOptions<T> {
+    source: T,
+    options: FillOptions,
+}
+
+impl FillOptions {
+    pub const fn new() -> FillOptions {
+        FillOptions {
+            packing: SubpixelPacking::RowPadded,
+            luminance: Luminance::Max,
+            quantizer: Quantizer::Truncate,
+            dithering: Dithering::None,
+        }
+    }
+
+    pub const fn with_packing(mut self, packing: SubpixelPacking) -> FillOptions {
+        self.packing = packing;
+
+        self
+    }
+
+    pub const fn with_luminance(mut self, luminance: Luminance) -> FillOptions {
+        self.luminance = luminance;
+
+        self
+    }
+
+    pub const fn with_quantizer(mut self, quantizer: Quantizer) -> FillOptions {
+        self.quantizer = quantizer;
+
+        self
+    }
+
+    pub const fn with_dithering(mut self, dithering: Dithering) -> FillOptions {
+        self.dithering = dithering;
+
+        self
+    }
+
+    pub const fn packing(&self) -> SubpixelPacking {
+        self.packing
+    }
+
+    pub const fn luminance(&self) -> Luminance {
+        self.luminance
+    }
+
+    pub const fn quantizer(&self) -> Quantizer {
+        self.quantizer
+    }
+
+    pub const fn dithering(&self) -> Dithering {
+        self.dithering
+    }
+
+    /// Gives the quantizer of the pixel at the given absolute position, taking the dithering in
+    /// account.
+    pub const fn quantizer_at(&self, x: u32, y: u32) -> Quantizer {
+        match self.dithering.threshold(x, y) {
+            Some(threshold) => Quantizer::Threshold(threshold),
+            None => self.quantizer,
+        }
+    }
+}
+
+impl<T> WithOptions<T> {
+    pub const fn new(source: T, options: FillOptions) -> WithOptions<T> {
+        WithOptions { source, options }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+}
+
+impl<T: IntoPixelIter> IntoPixelIter for WithOptions<T> {
+    type IntoIter = T::IntoIter;
+    type Item = T::Item;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        self.source.into_pixel_iter(x, y, width, height)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        self.source.check_area(x, y, width, height)
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        self.options
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        self.source.palette()
+    }
+
+    fn native_mode(&self) -> Option<ColorMode> {
+        self.source.native_mode()
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        self.source.native_row(x, y, width)
+    }
+}
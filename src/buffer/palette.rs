@@ -0,0 +1,344 @@
+use super::{Color, ColorMode, Component, FillOptions, IntoPixelIter};
+use crate::{CoreError, CoreResult};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Colors used by the indexed color modes.
+///
+/// The palette is shared, cloning it is cheap.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: Arc<[[u8; 3]]>,
+}
+
+/// Nearest color lookup in a [`Palette`], remembering the colors already met.
+#[derive(Debug)]
+pub struct NearestColor<'a> {
+    palette: &'a Palette,
+    cache: HashMap<[u8; 3], u8>,
+}
+
+/// Pixel source using a [`Palette`] for the indexed color modes.
+///
+/// Built by [`IntoPixelIter::with_palette`].
+#[derive(Clone, Debug)]
+pub struct WithPalette<T> {
+    source: T,
+    palette: Palette,
+}
+
+impl Palette {
+    pub const MAX_LENGTH: usize = 256;
+
+    /// Creates a palette from its colors, the index of a color being its position.
+    ///
+    /// The palette must hold between 1 and [`Palette::MAX_LENGTH`] colors.
+    pub fn new(colors: &[[u8; 3]]) -> CoreResult<Palette> {
+        CoreError::check_palette(colors.len(), Palette::MAX_LENGTH)?;
+
+        Ok(Palette {
+            colors: colors.into(),
+        })
+    }
+
+    /// Builds a palette of at most `length` colors representing the given area of a source, with
+    /// the median cut algorithm.
+    ///
+    /// The colors of the source are split in boxes, the box with the widest component range being
+    /// cut at its median until the expected number of boxes is reached. Each box then gives the
+    /// mean of its colors. The palette is shorter when the source has less distinct colors.
+    pub fn median_cut<T: IntoPixelIter>(
+        source: T,
+        width: u16,
+        height: u16,
+        length: usize,
+    ) -> CoreResult<Palette> {
+        CoreError::check_palette(length, Palette::MAX_LENGTH)?;
+
+        let mut histogram = HashMap::<[u8; 3], u64>::new();
+
+        for pixel in source.into_pixel_iter(0, 0, width, height) {
+            *histogram
+                .entry(pixel.components().map(Component::to_u8))
+                .or_default() += 1;
+        }
+
+        if histogram.is_empty() {
+            return Palette::new(&[[0, 0, 0]]);
+        }
+
+        let mut colors = histogram.into_iter().collect::<Vec<_>>();
+
+        colors.sort_unstable();
+
+        let mut boxes = vec![colors];
+
+        while boxes.len() < length {
+            let widest = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, colors)| colors.len() > 1)
+                .map(|(index, colors)| (index, widest_component(colors)))
+                .max_by_key(|(_, (_, range))| *range);
+
+            let Some((index, (component, _))) = widest else {
+                break;
+            };
+
+            let mut colors = boxes.swap_remove(index);
+
+            colors.sort_by_key(|(color, _)| color[component]);
+
+            let total = colors.iter().map(|(_, count)| count).sum::<u64>();
+            let mut count = 0;
+            let median = colors
+                .iter()
+                .position(|(_, pixels)| {
+                    count += pixels;
+
+                    count * 2 >= total
+                })
+                .unwrap_or(0)
+                .min(colors.len() - 2);
+
+            let upper = colors.split_off(median + 1);
+
+            boxes.push(colors);
+            boxes.push(upper);
+        }
+
+        Palette::new(&boxes.iter().map(|colors| mean(colors)).collect::<Vec<_>>())
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Gives the index of the color of the palette nearest to the given one, by euclidean
+    /// distance. The first color is chosen when several colors are at the same distance.
+    pub fn nearest(&self, color: [u8; 3]) -> u8 {
+        let distance = |entry: &[u8; 3]| -> u32 {
+            (0..3)
+                .map(|i| (entry[i] as i32 - color[i] as i32).pow(2) as u32)
+                .sum()
+        };
+
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| distance(entry))
+            .map_or(0, |(index, _)| index as u8)
+    }
+}
+
+/// Gives the component with the widest range in a set of colors, and its range.
+fn widest_component(colors: &[([u8; 3], u64)]) -> (usize, u8) {
+    (0..3)
+        .map(|component| {
+            let values = colors.iter().map(|(color, _)| color[component]);
+            let minimum = values.clone().min().unwrap_or(0);
+            let maximum = values.max().unwrap_or(0);
+
+            (component, maximum - minimum)
+        })
+        .rev()
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Gives the mean of a set of colors weighted by their number of pixels.
+fn mean(colors: &[([u8; 3], u64)]) -> [u8; 3] {
+    let total = colors.iter().map(|(_, count)| count).sum::<u64>();
+
+    std::array::from_fn(|component| {
+        let sum = colors
+            .iter()
+            .map(|(color, count)| color[component] as u64 * count)
+            .sum::<u64>();
+
+        ((sum + total / 2) / total) as u8
+    })
+}
+
+impl<'a> NearestColor<'a> {
+    pub fn new(palette: &'a Palette) -> NearestColor<'a> {
+        NearestColor {
+            palette,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Gives the index of the color of the palette nearest to the given one, see
+    /// [`Palette::nearest`].
+    pub fn index(&mut self, color: [u8; 3]) -> u8 {
+        *self
+            .cache
+            .entry(color)
+            .or_insert_with(|| self.palette.nearest(color))
+    }
+}
+
+impl<T> WithPalette<T> {
+    pub const fn new(source: T, palette: Palette) -> WithPalette<T> {
+        WithPalette { source, palette }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+}
+
+impl<T: IntoPixelIter> IntoPixelIter for WithPalette<T> {
+    type IntoIter = T::IntoIter;
+    type Item = T::Item;
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        self.source.into_pixel_iter(x, y, width, height)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        self.source.check_area(x, y, width, height)
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        self.source.fill_options()
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        Some(self.palette.clone())
+    }
+
+    fn native_mode(&self) -> Option<ColorMode> {
+        self.source.native_mode()
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        self.source.native_row(x, y, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NearestColor, Palette};
+    use crate::test_helper::{DummyBuffer, COLOR_RED};
+    use crate::{Color, CoreError, IntoPixelIter};
+
+    struct Rgb([u8; 3]);
+
+    struct Pixels<'a>(&'a [[u8; 3]]);
+
+    impl Color for Rgb {
+        type Component = u8;
+
+        fn components(self) -> [u8; 3] {
+            self.0
+        }
+    }
+
+    impl<'a> IntoPixelIter for Pixels<'a> {
+        type IntoIter = std::iter::Map<std::slice::Iter<'a, [u8; 3]>, fn(&[u8; 3]) -> Rgb>;
+        type Item = Rgb;
+
+        fn into_pixel_iter(self, _x: u16, _y: u16, width: u16, height: u16) -> Self::IntoIter {
+            let length = width as usize * height as usize;
+
+            self.0[..length].iter().map(|color| Rgb(*color))
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            Palette::new(&[]),
+            Err(CoreError::InvalidPalette {
+                given: 0,
+                maximum: 256
+            })
+        );
+        assert_eq!(
+            Palette::new(&[[0, 0, 0]; 257]),
+            Err(CoreError::InvalidPalette {
+                given: 257,
+                maximum: 256
+            })
+        );
+        assert_eq!(Palette::new(&[[0, 0, 0]; 256]).unwrap().len(), 256);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let palette =
+            Palette::new(&[[0, 0, 0], [0xFF, 0xFF, 0xFF], [0xFF, 0, 0], [0, 0, 0]]).unwrap();
+
+        assert_eq!(palette.nearest([0x10, 0x20, 0x10]), 0);
+        assert_eq!(palette.nearest([0xC0, 0xB0, 0xF0]), 1);
+        assert_eq!(palette.nearest([0xC0, 0x40, 0x50]), 2);
+        assert_eq!(palette.nearest([0x80, 0x80, 0x80]), 1);
+
+        let mut nearest = NearestColor::new(&palette);
+
+        for color in [[0x10, 0x20, 0x10], [0xC0, 0x40, 0x50], [0x10, 0x20, 0x10]] {
+            assert_eq!(nearest.index(color), palette.nearest(color));
+        }
+
+        assert_eq!(nearest.cache.len(), 2);
+    }
+
+    #[test]
+    fn test_median_cut() {
+        const COLORS: [[u8; 3]; 4] = [
+            [0x00, 0x00, 0x00],
+            [0xFF, 0x00, 0x00],
+            [0x00, 0xFF, 0x00],
+            [0x00, 0x00, 0xFF],
+        ];
+
+        let image = [COLORS, COLORS, COLORS].concat();
+
+        // Enough colors for the whole source
+        let palette = Palette::median_cut(Pixels(&image), 4, 3, 16).unwrap();
+        let mut colors = palette.colors().to_vec();
+
+        colors.sort();
+
+        assert_eq!(colors, [COLORS[0], COLORS[3], COLORS[2], COLORS[1]]);
+
+        // Two clusters of colors
+        let image = [
+            [0x00, 0x00, 0x00],
+            [0x10, 0x10, 0x10],
+            [0x10, 0x10, 0x10],
+            [0xF0, 0xF0, 0xF0],
+            [0xE0, 0xE0, 0xE0],
+            [0xE0, 0xE0, 0xE0],
+        ];
+
+        let palette = Palette::median_cut(Pixels(&image), 3, 2, 2).unwrap();
+        let mut colors = palette.colors().to_vec();
+
+        colors.sort();
+
+        assert_eq!(colors, [[0x0B, 0x0B, 0x0B], [0xE5, 0xE5, 0xE5]]);
+
+        // Only the read area is used
+        let palette = Palette::median_cut(Pixels(&image), 3, 1, 8).unwrap();
+
+        assert_eq!(palette.len(), 2);
+
+        let palette = Palette::median_cut(&DummyBuffer::new(COLOR_RED), 5, 5, 8).unwrap();
+
+        assert_eq!(palette.colors(), [[0xFF, 0x00, 0x00]]);
+
+        let palette = Palette::median_cut(Pixels(&image), 0, 0, 8).unwrap();
+
+        assert_eq!(palette.colors(), [[0x00, 0x00, 0x00]]);
+    }
+}
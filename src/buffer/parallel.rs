@@ -0,0 +1,293 @@
+use super::{calculate_length, Dithering, FillTarget, PixelFormat, PixelSource, SubpixelPacking};
+use crate::{CoreError, CoreResult};
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Number of pixels below which an area is converted on the calling thread, the threads costing
+/// more than they save.
+pub const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+/// Converts an area in bands of rows, each band being converted on its own thread.
+///
+/// The area stays converted on the calling thread when it is smaller than
+/// [`PARALLEL_THRESHOLD`], when the error diffusion spreads the error from a row to the next one
+/// or when the pixels of several rows are packed together.
+pub(super) fn encode_parallel<S, F>(
+    source: S,
+    format: &F,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+) -> CoreResult<()>
+where
+    S: PixelSource + Clone + Send,
+    F: PixelFormat + Sync,
+{
+    let options = source.fill_options();
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(height as usize);
+
+    let serial = calculate_length(width, height) < PARALLEL_THRESHOLD
+        || threads < 2
+        || matches!(options.dithering(), Dithering::Diffusion { .. })
+        || (options.packing() == SubpixelPacking::Continuous && format.storage().group().0 > 1);
+
+    if serial {
+        return format.encode(source, target, x, y, width, height);
+    }
+
+    encode_bands(source, format, target, x, y, width, height, threads)
+}
+
+/// Converts an area in the given number of bands of rows, each on its own thread.
+#[allow(clippy::too_many_arguments)]
+fn encode_bands<S, F>(
+    source: S,
+    format: &F,
+    target: FillTarget,
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    threads: usize,
+) -> CoreResult<()>
+where
+    S: PixelSource + Clone + Send,
+    F: PixelFormat + Sync,
+{
+    let storage = format.storage();
+
+    CoreError::check_storage(storage)?;
+
+    let row_length = storage.buffer_length(width, 1, SubpixelPacking::RowPadded);
+    let band_height = height.div_ceil(threads as u16).max(1);
+    let bands = target.bands(row_length, height as usize, band_height as usize)?;
+
+    thread::scope(|scope| {
+        let workers = bands
+            .into_iter()
+            .zip((0..height).step_by(band_height as usize))
+            .map(|(band, row)| {
+                let source = source.clone();
+                let rows = band_height.min(height - row);
+
+                scope.spawn(move || format.encode(source, band, x, y + row, width, rows))
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .try_for_each(|worker| match worker.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_bands, FillTarget, PARALLEL_THRESHOLD};
+    use crate::{
+        Buffer, Color, ColorMode, CoreError, CoreResult, DiffusionKernel, Dithering, FillOptions,
+        IntoPixelIter, Palette, PixelFormat, PixelSlice, StorageMode, SubpixelPacking,
+        TargetLayout,
+    };
+
+    const WIDTH: u16 = 320;
+    const HEIGHT: u16 = 240;
+
+    fn pixels() -> Vec<[u8; 3]> {
+        (0..WIDTH as usize * HEIGHT as usize)
+            .map(|i| [(i % 251) as u8, (i / 320) as u8, (i * 7 % 256) as u8])
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_bands() {
+        let pixels = pixels();
+        let palette = Palette::new(&[[0, 0, 0], [0xFF, 0x80, 0x00], [0x20, 0xFF, 0xC0]]).unwrap();
+        let options = [
+            FillOptions::new(),
+            FillOptions::new().with_dithering(Dithering::Bayer8x8),
+            FillOptions::new().with_packing(SubpixelPacking::Continuous),
+        ];
+        let modes = [
+            ColorMode::RGB,
+            ColorMode::RGBA8888,
+            ColorMode::RGB444,
+            ColorMode::RGB565BE,
+            ColorMode::BGR565LE,
+            ColorMode::Grayscale1Bit,
+            ColorMode::Grayscale4Bit,
+            ColorMode::Grayscale16BitBE,
+            ColorMode::Indexed2Bit,
+        ];
+
+        // An area which is not aligned on the frame, with an odd height
+        let (x, y, width, height) = (3, 5, 61, 37);
+
+        for options in options {
+            for mode in modes {
+                if options.packing() == SubpixelPacking::Continuous
+                    && !matches!(mode.byte_size(), StorageMode::BytesPerPixel(_))
+                {
+                    continue;
+                }
+
+                let source = PixelSlice::new(&pixels, WIDTH)
+                    .with_options(options)
+                    .with_palette(palette.clone());
+                let length = mode
+                    .byte_size()
+                    .buffer_length(width, height, options.packing());
+                let mut expected = vec![0u8; length];
+
+                source
+                    .clone()
+                    .fill(mode, &mut expected, x, y, width, height)
+                    .unwrap();
+
+                for threads in [1, 2, 3, 8, 37, 50] {
+                    let mut buffer = vec![0u8; length];
+
+                    encode_bands(
+                        source.clone(),
+                        &mode,
+                        FillTarget::packed(&mut buffer),
+                        x,
+                        y,
+                        width,
+                        height,
+                        threads,
+                    )
+                    .unwrap();
+
+                    assert_eq!(buffer, expected, "{mode:?} {options:?} {threads}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_bands_strided() {
+        let pixels = pixels();
+        let source = PixelSlice::new(&pixels, WIDTH);
+        let layout = TargetLayout::new(50).with_offset(7);
+        let mut expected = vec![0u8; 7 + 50 * 9 + 40];
+        let mut buffer = expected.clone();
+
+        source
+            .fill_strided(ColorMode::RGB565LE, &mut expected, layout, 1, 2, 20, 10)
+            .unwrap();
+        encode_bands(
+            source,
+            &ColorMode::RGB565LE,
+            FillTarget::with_layout(&mut buffer, layout),
+            1,
+            2,
+            20,
+            10,
+            4,
+        )
+        .unwrap();
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_fill_parallel() {
+        let pixels = pixels();
+        let source = PixelSlice::new(&pixels, WIDTH).with_options(
+            FillOptions::new().with_dithering(Dithering::Diffusion {
+                kernel: DiffusionKernel::FloydSteinberg,
+                serpentine: false,
+            }),
+        );
+
+        for options in [FillOptions::new(), source.fill_options()] {
+            let source = source.with_options(options);
+            let mut expected = vec![0u8; 2 * WIDTH as usize * HEIGHT as usize];
+            let mut buffer = expected.clone();
+
+            source
+                .fill_bgr565be(&mut expected, 0, 0, WIDTH, HEIGHT)
+                .unwrap();
+            source
+                .fill_parallel(ColorMode::BGR565BE, &mut buffer, 0, 0, WIDTH, HEIGHT)
+                .unwrap();
+
+            assert_eq!(buffer, expected, "{options:?}");
+        }
+    }
+
+    #[test]
+    fn test_fill_parallel_errors() {
+        let pixels = pixels();
+        let source = PixelSlice::new(&pixels, WIDTH);
+
+        assert!(WIDTH as usize * HEIGHT as usize >= PARALLEL_THRESHOLD);
+        assert_eq!(
+            source.fill_parallel(ColorMode::RGB565LE, &mut [0; 10], 0, 0, WIDTH, HEIGHT),
+            Err(CoreError::BufferSizeError {
+                given: 10,
+                expected: 2 * WIDTH as usize * HEIGHT as usize
+            })
+        );
+        assert_eq!(
+            source.fill_parallel(ColorMode::Indexed8Bit, &mut [0; 10], 0, 0, 5, 2),
+            Err(CoreError::MissingPalette)
+        );
+
+        let storage = StorageMode::PixelGroup {
+            pixels: 0,
+            bytes: 2,
+        };
+
+        assert_eq!(
+            source.fill_parallel(EmptyGroup, &mut [0; 10], 0, 0, WIDTH, HEIGHT),
+            Err(CoreError::UnsupportedStorage { storage })
+        );
+        assert_eq!(
+            encode_bands(
+                source,
+                &EmptyGroup,
+                FillTarget::packed(&mut [0; 10]),
+                0,
+                0,
+                WIDTH,
+                HEIGHT,
+                4
+            ),
+            Err(CoreError::UnsupportedStorage { storage })
+        );
+    }
+
+    /// A format whose groups hold no pixel.
+    struct EmptyGroup;
+
+    impl PixelFormat for EmptyGroup {
+        fn storage(&self) -> StorageMode {
+            StorageMode::PixelGroup {
+                pixels: 0,
+                bytes: 2,
+            }
+        }
+
+        fn encode_pixel<C: Color>(
+            &self,
+            _pixel: C,
+            _options: &FillOptions,
+            _palette: Option<&Palette>,
+            _position: (u32, u32),
+        ) -> CoreResult<u64> {
+            Ok(0)
+        }
+
+        fn decode_pixel(&self, _code: u64, _palette: Option<&Palette>) -> CoreResult<[u8; 3]> {
+            Ok([0, 0, 0])
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use super::frame_buffer::check_frame_area;
+use super::{Color, FrameBufferIter, IntoPixelIter};
+use crate::{CoreError, CoreResult};
+
+/// Image borrowed from a slice of pixels stored row after row, such as raw image data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelSlice<'a, P> {
+    pixels: &'a [P],
+    width: u16,
+}
+
+impl<'a, P> PixelSlice<'a, P> {
+    /// Creates an image of the given width. A last incomplete row is ignored.
+    pub const fn new(pixels: &'a [P], width: u16) -> PixelSlice<'a, P> {
+        PixelSlice { pixels, width }
+    }
+
+    pub const fn pixels(&self) -> &'a [P] {
+        self.pixels
+    }
+
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+}
+
+impl<'a> PixelSlice<'a, [u8; 3]> {
+    /// Creates an image from RGB888 data, three bytes per pixel.
+    pub fn from_rgb_bytes(bytes: &'a [u8], width: u16) -> CoreResult<PixelSlice<'a, [u8; 3]>> {
+        let (pixels, remainder) = bytes.as_chunks::<3>();
+
+        if !remainder.is_empty() {
+            return Err(CoreError::BufferSizeError {
+                given: bytes.len(),
+                expected: pixels.len() * 3,
+            });
+        }
+
+        Ok(PixelSlice::new(pixels, width))
+    }
+}
+
+impl<'a, P: Color + Copy> IntoPixelIter for PixelSlice<'a, P> {
+    type IntoIter = FrameBufferIter<'a, P>;
+    type Item = P;
+
+    /// Gives the pixels of the area, or none when the area goes past the image.
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        FrameBufferIter::new(self.pixels, self.width, x, y, width, height)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        let frame_width = self.width as usize;
+        let frame_height = self.pixels.len().checked_div(frame_width).unwrap_or(0);
+
+        check_frame_area(frame_width, frame_height, x, y, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PixelSlice;
+    use crate::{Buffer, CoreError, IntoPixelIter};
+
+    #[test]
+    fn test_into_pixel_iter() {
+        let pixels = (0..12u8).map(|i| [i, i, i]).collect::<Vec<_>>();
+        let area = |x, y, width, height| {
+            PixelSlice::new(&pixels, 4)
+                .into_pixel_iter(x, y, width, height)
+                .map(|[value, _, _]| value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(area(0, 0, 4, 3), (0..12).collect::<Vec<_>>());
+        assert_eq!(area(1, 1, 2, 2), [5, 6, 9, 10]);
+        assert_eq!(area(3, 2, 1, 1), [11]);
+
+        // Going past the image
+        assert_eq!(area(3, 2, 2, 2), []);
+        assert_eq!(area(0, 3, 4, 1), []);
+
+        // The incomplete row is ignored
+        let image = PixelSlice::new(&pixels[..10], 4);
+
+        assert_eq!(image.into_pixel_iter(0, 1, 4, 1).count(), 4);
+        assert_eq!(image.into_pixel_iter(0, 1, 4, 2).count(), 0);
+    }
+
+    #[test]
+    fn test_fill_out_of_bounds() {
+        let pixels = (0..12u8).map(|i| [i, i, i]).collect::<Vec<_>>();
+        let image = PixelSlice::new(&pixels, 4);
+        let mut buffer = [0xEEu8; 4];
+
+        assert_eq!(
+            image.fill_grayscale_8bit(&mut buffer, 3, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 3,
+                y: 0,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(
+            image.fill_grayscale_8bit(&mut buffer, 0, 2, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 0,
+                y: 2,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(buffer, [0xEE; 4]);
+
+        image.fill_grayscale_8bit(&mut buffer, 2, 1, 2, 2).unwrap();
+        assert_eq!(buffer, [6, 7, 10, 11]);
+    }
+
+    #[test]
+    fn test_from_rgb_bytes() {
+        let bytes = [
+            0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00, // First row
+            0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, // Second row
+        ];
+        let mut buffer = [0u8; 4];
+
+        PixelSlice::from_rgb_bytes(&bytes, 2)
+            .unwrap()
+            .fill_rgb565be(&mut buffer, 0, 1, 2, 1)
+            .unwrap();
+        assert_eq!(buffer, [0x00, 0x1F, 0xFF, 0xFF]);
+
+        assert_eq!(
+            PixelSlice::from_rgb_bytes(&bytes[..11], 2),
+            Err(CoreError::BufferSizeError {
+                given: 11,
+                expected: 9
+            })
+        );
+    }
+}
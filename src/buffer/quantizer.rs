@@ -0,0 +1,99 @@
+use super::Component;
+
+/// Policy used to reduce a component to the few bits of the packed color modes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Quantizer {
+    /// Keeps the most significant bits. Every color is rounded down, so the image is slightly
+    /// darker.
+    #[default]
+    Truncate,
+    /// Scales the component to the target range and rounds to the nearest level. The extremes of
+    /// the source range give the extremes of the target one.
+    RoundToNearest,
+    /// Adds half a target step before truncating and clamps the values overflowing the target
+    /// range. Each level covers the same amount of source values, except the highest one.
+    RoundWithClamp,
+    /// Scales the component to the target range, adds the given fraction of a step (in 1/65536)
+    /// and rounds down. This is the building block of the ordered dithering.
+    Threshold(u16),
+}
+
+impl Quantizer {
+    /// Reduces a component to `bits` bits, `bits` being between 1 and 8.
+    pub fn quantize<C: Component>(&self, component: C, bits: u32) -> u8 {
+        match self {
+            Quantizer::Truncate => component.most_significant_byte() >> (8 - bits),
+            Quantizer::RoundToNearest => component.scale(bits) as u8,
+            Quantizer::RoundWithClamp => {
+                let max = (1u32 << bits) - 1;
+                let half_step = 1u32 << (15 - bits);
+                let value = (component.to_u16() as u32 + half_step) >> (16 - bits);
+
+                value.min(max) as u8
+            }
+            Quantizer::Threshold(threshold) => {
+                let max = (1u64 << bits) - 1;
+                let value = component.to_u16() as u64 * max * 0x10000 + *threshold as u64 * 0xFFFF;
+
+                (value / (0xFFFF * 0x10000)).min(max) as u8
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quantizer;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(Quantizer::Truncate.quantize(0x00u8, 5), 0);
+        assert_eq!(Quantizer::Truncate.quantize(0x07u8, 5), 0);
+        assert_eq!(Quantizer::Truncate.quantize(0x08u8, 5), 1);
+        assert_eq!(Quantizer::Truncate.quantize(0xFFu8, 5), 31);
+        assert_eq!(Quantizer::Truncate.quantize(0xFFu8, 6), 63);
+        assert_eq!(Quantizer::Truncate.quantize(0xFBFFu16, 6), 62);
+    }
+
+    #[test]
+    fn test_round_to_nearest() {
+        assert_eq!(Quantizer::RoundToNearest.quantize(0x00u8, 5), 0);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0x04u8, 5), 0);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0x05u8, 5), 1);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0x80u8, 5), 16);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0xFBu8, 5), 31);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0xFFu8, 5), 31);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0x80u8, 6), 32);
+        assert_eq!(Quantizer::RoundToNearest.quantize(0xFBFFu16, 6), 62);
+    }
+
+    #[test]
+    fn test_round_with_clamp() {
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0x00u8, 5), 0);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0x03u8, 5), 0);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0x04u8, 5), 1);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0x80u8, 5), 16);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0xF3u8, 5), 30);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0xF4u8, 5), 31);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0xFFu8, 5), 31);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0xFFu8, 6), 63);
+        assert_eq!(Quantizer::RoundWithClamp.quantize(0xFBFFu16, 6), 63);
+    }
+
+    #[test]
+    fn test_threshold() {
+        assert_eq!(Quantizer::Threshold(0).quantize(0x00u8, 5), 0);
+        assert_eq!(Quantizer::Threshold(0xFFFF).quantize(0x00u8, 5), 0);
+        assert_eq!(Quantizer::Threshold(0).quantize(0xFFu8, 5), 31);
+        assert_eq!(Quantizer::Threshold(0xFFFF).quantize(0xFFu8, 5), 31);
+
+        // 0x80 is 15.56 steps of 5 bits, 31.62 steps of 6 bits and 0.502 step of 1 bit
+        assert_eq!(Quantizer::Threshold(0x7000).quantize(0x80u8, 5), 15);
+        assert_eq!(Quantizer::Threshold(0x7200).quantize(0x80u8, 5), 16);
+        assert_eq!(Quantizer::Threshold(0x6000).quantize(0x80u8, 6), 31);
+        assert_eq!(Quantizer::Threshold(0x6200).quantize(0x80u8, 6), 32);
+        assert_eq!(Quantizer::Threshold(0x7F80).quantize(0x80u8, 1), 1);
+        assert_eq!(Quantizer::Threshold(0x7F00).quantize(0x80u8, 1), 0);
+        assert_eq!(Quantizer::Threshold(0x8000).quantize(0x7Fu8, 1), 0);
+    }
+}
@@ -0,0 +1,718 @@
+use super::frame_buffer::check_frame_area;
+use super::{Color, ColorMode, Component, FillOptions, IntoPixelIter, Palette};
+use crate::CoreResult;
+use std::f64::consts::PI;
+
+/// Filter interpolating the pixels of a scaled image.
+///
+/// When an image is reduced, the filters other than [`ResizeFilter::Nearest`] are widened to
+/// average all the source pixels covered by a scaled pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Takes the closest source pixel, keeping the hard edges of pixel art.
+    Nearest,
+    /// Interpolates linearly between the 2 closest pixels of each axis.
+    #[default]
+    Bilinear,
+    /// Catmull-Rom spline through the 4 closest pixels of each axis, sharper than the bilinear
+    /// filter.
+    Bicubic,
+    /// Windowed sinc over the 6 closest pixels of each axis, the sharpest of the filters.
+    Lanczos3,
+}
+
+/// Placement of the scaled image in the frame requested.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// Scales each axis to the frame, distorting the image unless the aspect ratios match.
+    #[default]
+    Stretch,
+    /// Scales the image to fit in the frame while keeping its aspect ratio. The resized source is
+    /// then as large as the scaled image, smaller than the frame on one axis.
+    Fit,
+    /// Scales the image to cover the frame while keeping its aspect ratio, the overflowing borders
+    /// being cropped evenly.
+    Fill,
+    /// Scales the image as [`ResizeMode::Fit`] and centers it in the frame, the bars left being
+    /// filled with the background color.
+    Letterbox,
+}
+
+/// Pixel source giving a frame of another source scaled to other dimensions.
+///
+/// The pixels are interpolated with [`f32`] components, given as `[f32; 3]` colors. As for
+/// [`Transformed`](super::Transformed), the dimensions of the source frame are needed and the
+/// pixels of the source covered by an area are gathered before being resampled. The missing
+/// pixels of a source ending early are black, and an area going past the resized frame gives no
+/// pixels. Built by [`IntoPixelIter::resize`].
+#[derive(Clone, Copy, Debug)]
+pub struct Resized<T> {
+    source: T,
+    source_width: u16,
+    source_height: u16,
+    width: u16,
+    height: u16,
+    filter: ResizeFilter,
+    mode: ResizeMode,
+    background: [f32; 3],
+}
+
+/// Iterator over an area of a [`Resized`] source.
+pub struct ResizedIter {
+    pixels: std::vec::IntoIter<[f32; 3]>,
+}
+
+/// Size and position of the scaled image in the frame, the position being negative when the
+/// image is cropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Placement {
+    frame_width: u16,
+    frame_height: u16,
+    scaled_width: u32,
+    scaled_height: u32,
+    left: i64,
+    top: i64,
+}
+
+/// Weights of the consecutive source pixels, from `first`, making a pixel of the scaled image on
+/// one axis.
+#[derive(Clone, Debug, PartialEq)]
+struct Taps {
+    first: usize,
+    weights: Vec<f32>,
+}
+
+impl ResizeFilter {
+    /// Gives the distance from the center beyond which the weight is null, in pixels.
+    const fn support(&self) -> f64 {
+        match self {
+            ResizeFilter::Nearest => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// Gives the weight of a source pixel at the given distance from the center.
+    fn weight(&self, distance: f64) -> f64 {
+        let distance = distance.abs();
+
+        match self {
+            ResizeFilter::Nearest => {
+                if distance < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Bilinear => (1.0 - distance).max(0.0),
+            ResizeFilter::Bicubic => {
+                if distance < 1.0 {
+                    (1.5 * distance - 2.5) * distance * distance + 1.0
+                } else if distance < 2.0 {
+                    ((-0.5 * distance + 2.5) * distance - 4.0) * distance + 2.0
+                } else {
+                    0.0
+                }
+            }
+            ResizeFilter::Lanczos3 => {
+                if distance == 0.0 {
+                    1.0
+                } else if distance < 3.0 {
+                    let x = PI * distance;
+
+                    3.0 * x.sin() * (x / 3.0).sin() / (x * x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Gives the source pixels making the pixel `position` of an axis scaled from `source_length`
+    /// to `scaled_length` pixels.
+    fn taps(&self, source_length: u32, scaled_length: u32, position: u32) -> Taps {
+        let ratio = source_length as f64 / scaled_length as f64;
+        let center = (position as f64 + 0.5) * ratio;
+        let nearest = (center as usize).min(source_length as usize - 1);
+
+        if *self == ResizeFilter::Nearest {
+            return Taps {
+                first: nearest,
+                weights: vec![1.0],
+            };
+        }
+
+        let scale = ratio.max(1.0);
+        let support = self.support() * scale;
+        let first = (center - support).floor().max(0.0) as usize;
+        let last = ((center + support).ceil() as usize).min(source_length as usize);
+        let weights = (first..last)
+            .map(|index| self.weight((index as f64 + 0.5 - center) / scale))
+            .collect::<Vec<_>>();
+        let total = weights.iter().sum::<f64>();
+
+        if total.abs() < f64::EPSILON {
+            return Taps {
+                first: nearest,
+                weights: vec![1.0],
+            };
+        }
+
+        Taps {
+            first,
+            weights: weights
+                .into_iter()
+                .map(|weight| (weight / total) as f32)
+                .collect(),
+        }
+    }
+}
+
+impl Taps {
+    fn end(&self) -> usize {
+        self.first + self.weights.len()
+    }
+
+    /// Gives the weighted sum of the pixels, `pixel` giving the pixel at a source position.
+    fn apply(&self, pixel: impl Fn(usize) -> [f32; 3]) -> [f32; 3] {
+        self.weights
+            .iter()
+            .enumerate()
+            .fold([0.0; 3], |sum, (index, weight)| {
+                let pixel = pixel(self.first + index);
+
+                std::array::from_fn(|i| sum[i] + weight * pixel[i])
+            })
+    }
+}
+
+/// Gives the components of a color between 0 and 1.
+fn normalize<C: Color>(color: C) -> [f32; 3] {
+    color
+        .components()
+        .map(|component| component.to_u16() as f32 / u16::MAX as f32)
+}
+
+impl<T> Resized<T> {
+    /// Creates a source scaling a frame of `source_width` by `source_height` pixels to `width` by
+    /// `height` pixels, stretched with the bilinear filter over a black background.
+    pub const fn new(
+        source: T,
+        source_width: u16,
+        source_height: u16,
+        width: u16,
+        height: u16,
+    ) -> Resized<T> {
+        Resized {
+            source,
+            source_width,
+            source_height,
+            width,
+            height,
+            filter: ResizeFilter::Bilinear,
+            mode: ResizeMode::Stretch,
+            background: [0.0; 3],
+        }
+    }
+
+    pub const fn with_filter(mut self, filter: ResizeFilter) -> Resized<T> {
+        self.filter = filter;
+
+        self
+    }
+
+    pub const fn with_mode(mut self, mode: ResizeMode) -> Resized<T> {
+        self.mode = mode;
+
+        self
+    }
+
+    /// Sets the color of the bars left by [`ResizeMode::Letterbox`].
+    pub fn with_background<C: Color>(mut self, color: C) -> Resized<T> {
+        self.background = normalize(color);
+
+        self
+    }
+
+    pub const fn filter(&self) -> ResizeFilter {
+        self.filter
+    }
+
+    pub const fn mode(&self) -> ResizeMode {
+        self.mode
+    }
+
+    /// Gives the width of the resized frame, smaller than the requested one when the image is fit
+    /// in it.
+    pub fn width(&self) -> u16 {
+        self.placement().frame_width
+    }
+
+    /// Gives the height of the resized frame, smaller than the requested one when the image is
+    /// fit in it.
+    pub fn height(&self) -> u16 {
+        self.placement().frame_height
+    }
+
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    fn placement(&self) -> Placement {
+        let (width, height) = (self.width as u32, self.height as u32);
+        let stretched = Placement {
+            frame_width: self.width,
+            frame_height: self.height,
+            scaled_width: width,
+            scaled_height: height,
+            left: 0,
+            top: 0,
+        };
+
+        if self.source_width == 0 || self.source_height == 0 || self.mode == ResizeMode::Stretch {
+            return stretched;
+        }
+
+        let horizontal = width as f64 / self.source_width as f64;
+        let vertical = height as f64 / self.source_height as f64;
+        let scaled = |scale: f64, length: u16| (length as f64 * scale).round().max(1.0) as u32;
+
+        match self.mode {
+            ResizeMode::Stretch => stretched,
+            ResizeMode::Fit | ResizeMode::Letterbox => {
+                let scale = horizontal.min(vertical);
+                let scaled_width = scaled(scale, self.source_width).min(width);
+                let scaled_height = scaled(scale, self.source_height).min(height);
+
+                if self.mode == ResizeMode::Fit {
+                    Placement {
+                        frame_width: scaled_width as u16,
+                        frame_height: scaled_height as u16,
+                        scaled_width,
+                        scaled_height,
+                        left: 0,
+                        top: 0,
+                    }
+                } else {
+                    Placement {
+                        scaled_width,
+                        scaled_height,
+                        left: ((width - scaled_width) / 2) as i64,
+                        top: ((height - scaled_height) / 2) as i64,
+                        ..stretched
+                    }
+                }
+            }
+            ResizeMode::Fill => {
+                let scale = horizontal.max(vertical);
+                let scaled_width = scaled(scale, self.source_width).max(width);
+                let scaled_height = scaled(scale, self.source_height).max(height);
+
+                Placement {
+                    scaled_width,
+                    scaled_height,
+                    left: -(((scaled_width - width) / 2) as i64),
+                    top: -(((scaled_height - height) / 2) as i64),
+                    ..stretched
+                }
+            }
+        }
+    }
+
+    /// Gives the taps of the positions of an axis of the frame, `None` being outside of the
+    /// scaled image.
+    fn axis_taps(
+        &self,
+        source_length: u16,
+        scaled_length: u32,
+        offset: i64,
+        positions: std::ops::Range<u16>,
+    ) -> Vec<Option<Taps>> {
+        positions
+            .map(|position| {
+                let position = position as i64 - offset;
+
+                (source_length > 0 && (0..scaled_length as i64).contains(&position)).then(|| {
+                    self.filter
+                        .taps(source_length as u32, scaled_length, position as u32)
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T: IntoPixelIter> IntoPixelIter for Resized<T> {
+    type IntoIter = ResizedIter;
+    type Item = [f32; 3];
+
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        let placement = self.placement();
+        let (right, bottom) = match check_frame_area(
+            placement.frame_width as usize,
+            placement.frame_height as usize,
+            x,
+            y,
+            width,
+            height,
+        ) {
+            Ok(()) => (x + width, y + height),
+            Err(_) => (x, y),
+        };
+
+        let columns = self.axis_taps(
+            self.source_width,
+            placement.scaled_width,
+            placement.left,
+            x..right,
+        );
+        let rows = self.axis_taps(
+            self.source_height,
+            placement.scaled_height,
+            placement.top,
+            y..bottom,
+        );
+
+        // Area of the source covered by the requested area
+        let span = |taps: &[Option<Taps>]| {
+            taps.iter().flatten().fold(None, |span, taps| match span {
+                None => Some((taps.first, taps.end())),
+                Some((first, end)) => Some((taps.first.min(first), taps.end().max(end))),
+            })
+        };
+        let area = span(&columns).zip(span(&rows));
+
+        let mut pixels = Vec::with_capacity(columns.len() * rows.len());
+
+        let Some(((source_left, source_right), (source_top, source_bottom))) = area else {
+            pixels.resize(columns.len() * rows.len(), self.background);
+
+            return ResizedIter {
+                pixels: pixels.into_iter(),
+            };
+        };
+
+        let stride = source_right - source_left;
+        let mut source = self
+            .source
+            .into_pixel_iter(
+                source_left as u16,
+                source_top as u16,
+                stride as u16,
+                (source_bottom - source_top) as u16,
+            )
+            .map(normalize)
+            .collect::<Vec<_>>();
+
+        source.resize(stride * (source_bottom - source_top), [0.0; 3]);
+
+        // Scales the rows first, then the columns
+        let scaled_rows = source
+            .chunks_exact(stride)
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|taps| match taps {
+                        Some(taps) => taps.apply(|x| row[x - source_left]),
+                        None => self.background,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for row in &rows {
+            for (column, taps) in columns.iter().enumerate() {
+                let pixel = match (row, taps) {
+                    (Some(row), Some(_)) => row.apply(|y| scaled_rows[y - source_top][column]),
+                    _ => self.background,
+                };
+
+                pixels.push(pixel.map(|component| component.clamp(0.0, 1.0)));
+            }
+        }
+
+        ResizedIter {
+            pixels: pixels.into_iter(),
+        }
+    }
+
+    /// Checks the area against the resized frame, and the source frame against the source.
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        let placement = self.placement();
+
+        check_frame_area(
+            placement.frame_width as usize,
+            placement.frame_height as usize,
+            x,
+            y,
+            width,
+            height,
+        )?;
+        self.source
+            .check_area(0, 0, self.source_width, self.source_height)
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        self.source.fill_options()
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        self.source.palette()
+    }
+
+    // The pixels are interpolated, so the rows of the source are never copied
+    fn native_mode(&self) -> Option<ColorMode> {
+        None
+    }
+}
+
+impl Iterator for ResizedIter {
+    type Item = [f32; 3];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pixels.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pixels.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Placement, ResizeFilter, ResizeMode};
+    use crate::{Buffer, CoreError, FrameBuffer, IntoPixelIter, PixelSlice};
+
+    const FILTERS: [ResizeFilter; 4] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos3,
+    ];
+
+    fn rgb<T: IntoPixelIter<Item = [f32; 3]>>(
+        source: T,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Vec<[u8; 3]> {
+        source
+            .into_pixel_iter(x, y, width, height)
+            .map(|pixel| pixel.map(|component| (component * 255.0).round() as u8))
+            .collect()
+    }
+
+    fn gradient(width: u16, height: u16) -> FrameBuffer<[u8; 3]> {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [(x * 40) as u8, (y * 30) as u8, 0x80]))
+            .collect();
+
+        FrameBuffer::from_pixels(width, height, pixels).unwrap()
+    }
+
+    #[test]
+    fn test_same_size() {
+        let frame = gradient(5, 4);
+        let expected = frame.pixels().to_vec();
+
+        for filter in FILTERS {
+            let resized = (&frame).resize(5, 4, 5, 4).with_filter(filter);
+
+            assert_eq!(rgb(resized, 0, 0, 5, 4), expected, "{filter:?}");
+        }
+    }
+
+    #[test]
+    fn test_filters() {
+        let pixels = [[0u8; 3], [0xFF; 3]];
+        let source = PixelSlice::new(&pixels, 2);
+
+        assert_eq!(
+            rgb(
+                source.resize(2, 1, 4, 1).with_filter(ResizeFilter::Nearest),
+                0,
+                0,
+                4,
+                1
+            ),
+            [[0x00; 3], [0x00; 3], [0xFF; 3], [0xFF; 3]]
+        );
+        assert_eq!(
+            rgb(source.resize(2, 1, 4, 1), 0, 0, 4, 1),
+            [[0x00; 3], [0x40; 3], [0xBF; 3], [0xFF; 3]]
+        );
+
+        // The sharper filters overshoot on the edges, the components being clamped
+        for filter in [ResizeFilter::Bicubic, ResizeFilter::Lanczos3] {
+            let resized = rgb(source.resize(2, 1, 8, 1).with_filter(filter), 0, 0, 8, 1);
+
+            assert_eq!(resized[0], [0x00; 3], "{filter:?}");
+            assert_eq!(resized[7], [0xFF; 3], "{filter:?}");
+            assert!(
+                resized.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{filter:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reduce() {
+        let frame = FrameBuffer::new(7, 5, [0x12u8, 0x80, 0xED]);
+
+        for filter in FILTERS {
+            let resized = (&frame).resize(7, 5, 3, 2).with_filter(filter);
+
+            assert_eq!(
+                rgb(resized, 0, 0, 3, 2),
+                [[0x12, 0x80, 0xED]; 6],
+                "{filter:?}"
+            );
+        }
+
+        // The wide filters average all the pixels covered
+        let pixels = [[0u8; 3], [0xFE; 3], [0u8; 3], [0xFE; 3]];
+
+        assert_eq!(
+            rgb(PixelSlice::new(&pixels, 4).resize(4, 1, 1, 1), 0, 0, 1, 1),
+            [[0x7F; 3]]
+        );
+    }
+
+    #[test]
+    fn test_modes() {
+        let pixels = (0..8u8).map(|i| [i, i, i]).collect::<Vec<_>>();
+        let source = PixelSlice::new(&pixels, 4);
+        let resize = |width, height, mode| {
+            source
+                .resize(4, 2, width, height)
+                .with_filter(ResizeFilter::Nearest)
+                .with_mode(mode)
+        };
+
+        let fit = resize(2, 3, ResizeMode::Fit);
+
+        assert_eq!((fit.width(), fit.height()), (2, 1));
+        assert_eq!(rgb(fit, 0, 0, 2, 1), [[5; 3], [7; 3]]);
+
+        let fill = resize(2, 2, ResizeMode::Fill);
+
+        assert_eq!((fill.width(), fill.height()), (2, 2));
+        assert_eq!(rgb(fill, 0, 0, 2, 2), [[1; 3], [2; 3], [5; 3], [6; 3]]);
+
+        let letterbox = resize(2, 3, ResizeMode::Letterbox).with_background([0xFFu8, 0, 0]);
+        let bar = [0xFF, 0, 0];
+
+        assert_eq!((letterbox.width(), letterbox.height()), (2, 3));
+        assert_eq!(
+            rgb(letterbox, 0, 0, 2, 3),
+            [bar, bar, [5; 3], [7; 3], bar, bar]
+        );
+    }
+
+    #[test]
+    fn test_placement() {
+        let frame = gradient(400, 100);
+        let placement = |mode| {
+            (&frame)
+                .resize(400, 100, 320, 240)
+                .with_mode(mode)
+                .placement()
+        };
+
+        assert_eq!(
+            placement(ResizeMode::Letterbox),
+            Placement {
+                frame_width: 320,
+                frame_height: 240,
+                scaled_width: 320,
+                scaled_height: 80,
+                left: 0,
+                top: 80,
+            }
+        );
+        assert_eq!(
+            placement(ResizeMode::Fill),
+            Placement {
+                frame_width: 320,
+                frame_height: 240,
+                scaled_width: 960,
+                scaled_height: 240,
+                left: -320,
+                top: 0,
+            }
+        );
+        assert_eq!(
+            placement(ResizeMode::Fit),
+            Placement {
+                frame_width: 320,
+                frame_height: 80,
+                scaled_width: 320,
+                scaled_height: 80,
+                left: 0,
+                top: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_area() {
+        let frame = gradient(6, 5);
+
+        for filter in FILTERS {
+            for mode in [ResizeMode::Stretch, ResizeMode::Fill, ResizeMode::Letterbox] {
+                let resized = (&frame)
+                    .resize(6, 5, 11, 7)
+                    .with_filter(filter)
+                    .with_mode(mode);
+                let whole = rgb(resized, 0, 0, 11, 7);
+                let expected = (2..6)
+                    .flat_map(|y| whole[y * 11 + 3..y * 11 + 8].to_vec())
+                    .collect::<Vec<_>>();
+
+                assert_eq!(rgb(resized, 3, 2, 5, 4), expected, "{filter:?} {mode:?}");
+
+                // Going past the frame
+                assert!(rgb(resized, 0, 0, 100, 100).is_empty());
+                assert!(rgb(resized, 11, 0, 1, 1).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_out_of_bounds() {
+        let frame = gradient(6, 5);
+        let mut buffer = [0xEEu8; 12];
+
+        assert_eq!(
+            (&frame)
+                .resize(6, 5, 4, 3)
+                .fill_rgb(&mut buffer, 2, 0, 3, 1),
+            Err(CoreError::AreaOutOfBounds {
+                x: 2,
+                y: 0,
+                width: 3,
+                height: 1
+            })
+        );
+
+        // The source frame going past the source
+        assert_eq!(
+            (&frame)
+                .resize(6, 6, 4, 3)
+                .fill_rgb(&mut buffer, 0, 0, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 0,
+                y: 0,
+                width: 6,
+                height: 6
+            })
+        );
+        assert_eq!(buffer, [0xEE; 12]);
+
+        (&frame)
+            .resize(6, 5, 4, 3)
+            .fill_rgb(&mut buffer, 2, 1, 2, 2)
+            .unwrap();
+    }
+}
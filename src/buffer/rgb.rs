@@ -0,0 +1,134 @@
+use super::{Color, Quantizer};
+
+/// Color already encoded in RGB565, the red component being on the most significant bits.
+///
+/// The color is sent as is in the 565 color modes. The other color modes expand its components
+/// to 8 bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb565(pub u16);
+
+/// Expands a component stored on the given number of bits to 8 bits.
+const fn expand(level: u16, bits: u32) -> u8 {
+    let level = level as u32 & ((1 << bits) - 1);
+
+    ((level * 0xFF + ((1 << bits) - 1) / 2) / ((1 << bits) - 1)) as u8
+}
+
+impl Color for Rgb565 {
+    type Component = u8;
+
+    fn components(self) -> [u8; 3] {
+        [
+            expand(self.0 >> 11, 5),
+            expand(self.0 >> 5, 6),
+            expand(self.0, 5),
+        ]
+    }
+
+    fn into_rgb565_with(self, _quantizer: Quantizer) -> u16 {
+        self.0
+    }
+
+    fn into_bgr565_with(self, _quantizer: Quantizer) -> u16 {
+        self.0 >> 11 | (self.0 & 0x07E0) | self.0 << 11
+    }
+}
+
+impl Color for [u8; 3] {
+    type Component = u8;
+
+    fn components(self) -> [u8; 3] {
+        self
+    }
+}
+
+impl Color for (u8, u8, u8) {
+    type Component = u8;
+
+    fn components(self) -> [u8; 3] {
+        [self.0, self.1, self.2]
+    }
+}
+
+impl Color for [u16; 3] {
+    type Component = u16;
+
+    fn components(self) -> [u16; 3] {
+        self
+    }
+}
+
+impl Color for [f32; 3] {
+    type Component = f32;
+
+    fn components(self) -> [f32; 3] {
+        self
+    }
+}
+
+impl Color for [f64; 3] {
+    type Component = f64;
+
+    fn components(self) -> [f64; 3] {
+        self
+    }
+}
+
+/// Color packed as `0xRRGGBB`, the most significant byte being ignored.
+impl Color for u32 {
+    type Component = u8;
+
+    fn components(self) -> [u8; 3] {
+        [(self >> 16) as u8, (self >> 8) as u8, self as u8]
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::Rgb565;
+    use crate::{Color, Quantizer};
+
+    fn rgb(color: impl Color) -> [u8; 3] {
+        let mut buffer = [0u8; 3];
+
+        color.fill_rgb(&mut buffer).unwrap();
+
+        buffer
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(rgb([0x12u8, 0x34, 0x56]), [0x12, 0x34, 0x56]);
+        assert_eq!(rgb((0x12, 0x34, 0x56)), [0x12, 0x34, 0x56]);
+        assert_eq!(rgb(0xFF123456u32), [0x12, 0x34, 0x56]);
+        assert_eq!(rgb([0x1280u16, 0x3400, 0x56FF]), [0x12, 0x34, 0x57]);
+
+        assert_eq!(rgb([1.0f32, 0.5, -1.0]), [0xFF, 0x80, 0x00]);
+        assert_eq!(rgb([f64::NAN, 0.2, 2.0]), [0x00, 0x33, 0xFF]);
+
+        assert_eq!([0xFFu8, 0x00, 0xFF].into_rgb565(), 0b11111_000000_11111);
+        assert_eq!(0x00FF00u32.into_bgr565(), 0b00000_111111_00000);
+    }
+
+    #[test]
+    fn test_rgb565() {
+        let color = Rgb565(0b10011_111001_00101);
+
+        assert_eq!(color.components(), [0x9C, 0xE7, 0x29]);
+        assert_eq!(color.into_rgb565(), 0b10011_111001_00101);
+        assert_eq!(color.into_bgr565(), 0b00101_111001_10011);
+        assert_eq!(
+            color.into_rgb565_with(Quantizer::Threshold(0xFFFF)),
+            0b10011_111001_00101
+        );
+
+        assert_eq!(Rgb565(0xFFFF).components(), [0xFF; 3]);
+        assert_eq!(Rgb565(0).components(), [0x00; 3]);
+
+        // The expanded components give back the same color
+        for value in (0..=0xFFFF).step_by(7) {
+            assert_eq!(Rgb565(value).components().into_rgb565(), value);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use super::{Color, ColorMode, FillOptions, IntoPixelIter, Palette};
+use crate::{CoreError, CoreResult};
+
+/// Source handing out the pixels of an area row by row, so the encoders convert a whole scanline
+/// at once.
+///
+/// Every [`IntoPixelIter`] is a source, its pixels being gathered row by row with
+/// [`IterScanlines`]. A source storing its rows in memory may rather implement this trait to walk
+/// them directly.
+pub trait PixelSource {
+    type Scanlines: Scanlines<Pixel = Self::Pixel>;
+    type Pixel: Color;
+
+    fn into_scanlines(self, x: u16, y: u16, width: u16, height: u16) -> Self::Scanlines;
+
+    /// Checks that the source can give every pixel of the area, see
+    /// [`IntoPixelIter::check_area`].
+    fn check_area(&self, _x: u16, _y: u16, _width: u16, _height: u16) -> CoreResult<()> {
+        Ok(())
+    }
+
+    /// Gives the options used to encode this source, see [`IntoPixelIter::fill_options`].
+    fn fill_options(&self) -> FillOptions {
+        FillOptions::new()
+    }
+
+    /// Gives the palette used to encode this source in the indexed color modes, see
+    /// [`IntoPixelIter::palette`].
+    fn palette(&self) -> Option<Palette> {
+        None
+    }
+
+    /// Gives the color mode the source already stores its pixels in, see
+    /// [`IntoPixelIter::native_mode`].
+    fn native_mode(&self) -> Option<ColorMode> {
+        None
+    }
+
+    /// Gives the bytes of a row in the native color mode, see [`IntoPixelIter::native_row`].
+    fn native_row(&self, _x: u16, _y: u16, _width: u16) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Rows of an area given by a [`PixelSource`], from top to bottom.
+pub trait Scanlines {
+    type Pixel: Color;
+    type Row<'a>: ExactSizeIterator<Item = Self::Pixel>
+    where
+        Self: 'a;
+
+    /// Gives the next row of the area, holding `width` pixels, or `None` once the area is over.
+    ///
+    /// The encoders fail with a [`CoreError::BufferSizeError`] on a row of another length, or
+    /// when the area is over before `height` rows.
+    fn next_row(&mut self) -> CoreResult<Option<Self::Row<'_>>>;
+}
+
+/// Gives the next row of an area `width` pixels wide, as the encoders expect it. The rows are
+/// only over early for an area without columns.
+pub(super) fn next_full_row<S: Scanlines>(
+    scanlines: &mut S,
+    width: u16,
+) -> CoreResult<Option<S::Row<'_>>> {
+    let expected = width as usize;
+
+    match scanlines.next_row()? {
+        Some(row) if row.len() == expected => Ok(Some(row)),
+        None if expected == 0 => Ok(None),
+        row => Err(CoreError::BufferSizeError {
+            given: row.map_or(0, |row| row.len()),
+            expected,
+        }),
+    }
+}
+
+/// Rows gathered from a pixel iterator, `width` pixels at a time.
+///
+/// The pixels are only counted, so a source giving rows narrower than the area is only noticed
+/// when it does not end on a whole row.
+#[derive(Clone, Debug)]
+pub struct IterScanlines<I: Iterator> {
+    pixels: I,
+    width: usize,
+    row: Vec<I::Item>,
+}
+
+impl<I: Iterator> IterScanlines<I> {
+    pub fn new(pixels: I, width: u16) -> IterScanlines<I> {
+        IterScanlines {
+            pixels,
+            width: width as usize,
+            row: Vec::with_capacity(width as usize),
+        }
+    }
+}
+
+impl<I> Scanlines for IterScanlines<I>
+where
+    I: Iterator,
+    I::Item: Color,
+{
+    type Pixel = I::Item;
+    type Row<'a>
+        = std::vec::Drain<'a, I::Item>
+    where
+        I: 'a;
+
+    fn next_row(&mut self) -> CoreResult<Option<Self::Row<'_>>> {
+        self.row.clear();
+        self.row.extend(self.pixels.by_ref().take(self.width));
+
+        match self.row.len() {
+            0 => Ok(None),
+            given if given < self.width => Err(CoreError::BufferSizeError {
+                given,
+                expected: self.width,
+            }),
+            _ => Ok(Some(self.row.drain(..))),
+        }
+    }
+}
+
+impl<T: IntoPixelIter> PixelSource for T {
+    type Scanlines = IterScanlines<T::IntoIter>;
+    type Pixel = T::Item;
+
+    fn into_scanlines(self, x: u16, y: u16, width: u16, height: u16) -> Self::Scanlines {
+        IterScanlines::new(self.into_pixel_iter(x, y, width, height), width)
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        IntoPixelIter::check_area(self, x, y, width, height)
+    }
+
+    fn fill_options(&self) -> FillOptions {
+        IntoPixelIter::fill_options(self)
+    }
+
+    fn palette(&self) -> Option<Palette> {
+        IntoPixelIter::palette(self)
+    }
+
+    fn native_mode(&self) -> Option<ColorMode> {
+        IntoPixelIter::native_mode(self)
+    }
+
+    fn native_row(&self, x: u16, y: u16, width: u16) -> Option<&[u8]> {
+        IntoPixelIter::native_row(self, x, y, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IterScanlines, PixelSource, Scanlines};
+    use crate::{CoreError, CoreResult, FillOptions, IntoPixelIter, Luminance, PixelSlice};
+
+    fn collect<S: Scanlines>(mut scanlines: S) -> CoreResult<Vec<Vec<S::Pixel>>> {
+        let mut rows = Vec::new();
+
+        while let Some(row) = scanlines.next_row()? {
+            rows.push(row.collect());
+        }
+
+        Ok(rows)
+    }
+
+    #[test]
+    fn test_iter_scanlines() {
+        let pixels = (0..6u8).map(|i| [i, i, i]);
+
+        assert_eq!(
+            collect(IterScanlines::new(pixels.clone(), 3)),
+            Ok(vec![
+                vec![[0, 0, 0], [1, 1, 1], [2, 2, 2]],
+                vec![[3, 3, 3], [4, 4, 4], [5, 5, 5]],
+            ])
+        );
+        assert_eq!(collect(IterScanlines::new(pixels, 0)), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_short_row() {
+        let mut scanlines = IterScanlines::new((0..7u8).map(|i| [i, i, i]), 3);
+
+        assert_eq!(scanlines.next_row().unwrap().unwrap().len(), 3);
+        assert_eq!(scanlines.next_row().unwrap().unwrap().len(), 3);
+        assert_eq!(
+            scanlines.next_row().map(|row| row.map(Vec::from_iter)),
+            Err(CoreError::BufferSizeError {
+                given: 1,
+                expected: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_pixel_iter_source() {
+        let pixels = (0..12u8).map(|i| [i, i, i]).collect::<Vec<_>>();
+        let options = FillOptions::new().with_luminance(Luminance::Average);
+        let source = PixelSlice::new(&pixels, 4).with_options(options);
+
+        assert_eq!(PixelSource::fill_options(&source), options);
+        assert_eq!(PixelSource::palette(&source), None);
+        assert_eq!(
+            collect(source.into_scanlines(1, 1, 2, 2)),
+            Ok(vec![
+                vec![[5, 5, 5], [6, 6, 6]],
+                vec![[9, 9, 9], [10, 10, 10]]
+            ])
+        );
+    }
+}
@@ -0,0 +1,724 @@
+use super::{Color, ColorMode};
+use crate::{CoreError, CoreResult};
+
+/// Byte layout of the pixels of a slice given to [`convert_slice`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceFormat {
+    /// Three bytes per pixel, red first.
+    RGB888,
+    /// Four bytes per pixel, red first. The alpha channel is ignored.
+    RGBA8888,
+}
+
+impl SliceFormat {
+    pub const fn bytes_per_pixel(&self) -> usize {
+        match self {
+            SliceFormat::RGB888 => 3,
+            SliceFormat::RGBA8888 => 4,
+        }
+    }
+}
+
+/// Encoding computed by the vector units, the 16 bits values being byte swapped for the big
+/// endian modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kernel {
+    Rgb565 { swap: bool },
+    Bgr565 { swap: bool },
+    Grayscale8,
+    Grayscale16 { swap: bool },
+}
+
+impl Kernel {
+    const fn from_mode(mode: ColorMode) -> Option<Kernel> {
+        match mode {
+            ColorMode::RGB565LE => Some(Kernel::Rgb565 { swap: false }),
+            ColorMode::RGB565BE => Some(Kernel::Rgb565 { swap: true }),
+            ColorMode::BGR565LE => Some(Kernel::Bgr565 { swap: false }),
+            ColorMode::BGR565BE => Some(Kernel::Bgr565 { swap: true }),
+            ColorMode::Grayscale8Bit => Some(Kernel::Grayscale8),
+            ColorMode::Grayscale16BitLE => Some(Kernel::Grayscale16 { swap: false }),
+            ColorMode::Grayscale16BitBE => Some(Kernel::Grayscale16 { swap: true }),
+            _ => None,
+        }
+    }
+
+    const fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Kernel::Grayscale8 => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Instruction set used to convert the slices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    Portable,
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Sse2,
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Avx2,
+    #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+    Neon,
+}
+
+impl Backend {
+    /// Gives the fastest instruction set supported by the running CPU.
+    fn detect() -> Backend {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+
+            if is_x86_feature_detected!("sse2") {
+                return Backend::Sse2;
+            }
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                return Backend::Neon;
+            }
+        }
+
+        Backend::Portable
+    }
+
+    /// Converts the leading pixels of the source, giving the number of pixels converted.
+    ///
+    /// The backend must be supported by the running CPU.
+    fn convert(
+        &self,
+        format: SliceFormat,
+        source: &[u8],
+        kernel: Kernel,
+        target: &mut [u8],
+    ) -> usize {
+        match self {
+            Backend::Portable => 0,
+            // SAFETY: the instruction set is detected by `Backend::detect`
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => unsafe { x86::convert_sse2(format, source, kernel, target) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => unsafe { x86::convert_avx2(format, source, kernel, target) },
+            #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+            Backend::Neon => unsafe { neon::convert(format, source, kernel, target) },
+        }
+    }
+}
+
+/// Converts a slice of RGB888 or RGBA8888 pixels into the given color mode, using the vector
+/// units of the CPU when available.
+///
+/// The RGB565/BGR565 and the 8 and 16 bits grayscale modes are supported. The result is the one
+/// of the [`Color`] implementation of `[u8; 3]` with the default quantizer and luminance model,
+/// that is truncated 565 components and the brightest component as luminance.
+///
+/// # Errors
+///
+/// [`CoreError::UnsupportedColorMode`] for the other color modes, [`CoreError::BufferSizeError`]
+/// when the source does not hold whole pixels or the target is not the size of the converted
+/// pixels.
+pub fn convert_slice(
+    format: SliceFormat,
+    source: &[u8],
+    mode: ColorMode,
+    target: &mut [u8],
+) -> CoreResult<()> {
+    convert_with(Backend::detect(), format, source, mode, target)
+}
+
+/// Tells whether [`convert_slice`] supports the given color mode.
+pub(super) fn supports(mode: ColorMode) -> bool {
+    Kernel::from_mode(mode).is_some()
+}
+
+fn convert_with(
+    backend: Backend,
+    format: SliceFormat,
+    source: &[u8],
+    mode: ColorMode,
+    target: &mut [u8],
+) -> CoreResult<()> {
+    let kernel = Kernel::from_mode(mode).ok_or(CoreError::UnsupportedColorMode { mode })?;
+    let pixels = source.len() / format.bytes_per_pixel();
+
+    CoreError::check_length(source, pixels * format.bytes_per_pixel())?;
+    CoreError::check_length(target, pixels * kernel.bytes_per_pixel())?;
+
+    let converted = backend.convert(format, source, kernel, target);
+
+    convert_portable(
+        format,
+        &source[converted * format.bytes_per_pixel()..],
+        mode,
+        &mut target[converted * kernel.bytes_per_pixel()..],
+    )
+}
+
+/// Converts the pixels one by one with their [`Color`] implementation.
+fn convert_portable(
+    format: SliceFormat,
+    source: &[u8],
+    mode: ColorMode,
+    target: &mut [u8],
+) -> CoreResult<()> {
+    let kernel = Kernel::from_mode(mode).ok_or(CoreError::UnsupportedColorMode { mode })?;
+
+    for (pixel, buf) in source
+        .chunks_exact(format.bytes_per_pixel())
+        .zip(target.chunks_exact_mut(kernel.bytes_per_pixel()))
+    {
+        let color = [pixel[0], pixel[1], pixel[2]];
+
+        match mode {
+            ColorMode::RGB565LE => color.fill_rgb565le(buf)?,
+            ColorMode::RGB565BE => color.fill_rgb565be(buf)?,
+            ColorMode::BGR565LE => color.fill_bgr565le(buf)?,
+            ColorMode::BGR565BE => color.fill_bgr565be(buf)?,
+            ColorMode::Grayscale8Bit => color.fill_grayscale_8bit(buf)?,
+            ColorMode::Grayscale16BitLE => color.fill_grayscale_16bit_le(buf)?,
+            ColorMode::Grayscale16BitBE => color.fill_grayscale_16bit_be(buf)?,
+            _ => return Err(CoreError::UnsupportedColorMode { mode }),
+        }
+    }
+
+    Ok(())
+}
+
+/// SSE2 and AVX2 backends.
+///
+/// The pixels are loaded in 32 bits lanes holding `R | G << 8 | B << 16`, encoded in the same
+/// lanes then narrowed to 16 or 8 bits.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::{Kernel, SliceFormat};
+
+    /// Packs the three bytes of an RGB888 pixel in a 32 bits lane.
+    #[inline]
+    fn lane(pixel: &[u8]) -> i32 {
+        pixel[0] as i32 | (pixel[1] as i32) << 8 | (pixel[2] as i32) << 16
+    }
+
+    #[target_feature(enable = "sse2")]
+    fn encode_sse2(pixels: __m128i, kernel: Kernel) -> __m128i {
+        let mask = |value: i32| _mm_set1_epi32(value);
+
+        let (value, swap) = match kernel {
+            Kernel::Rgb565 { swap } => (
+                _mm_or_si128(
+                    _mm_or_si128(
+                        _mm_and_si128(_mm_slli_epi32(pixels, 8), mask(0xF800)),
+                        _mm_and_si128(_mm_srli_epi32(pixels, 5), mask(0x07E0)),
+                    ),
+                    _mm_and_si128(_mm_srli_epi32(pixels, 19), mask(0x001F)),
+                ),
+                swap,
+            ),
+            Kernel::Bgr565 { swap } => (
+                _mm_or_si128(
+                    _mm_or_si128(
+                        _mm_and_si128(_mm_srli_epi32(pixels, 8), mask(0xF800)),
+                        _mm_and_si128(_mm_srli_epi32(pixels, 5), mask(0x07E0)),
+                    ),
+                    _mm_and_si128(_mm_srli_epi32(pixels, 3), mask(0x001F)),
+                ),
+                swap,
+            ),
+            Kernel::Grayscale8 | Kernel::Grayscale16 { .. } => {
+                let brightest = _mm_max_epu8(
+                    _mm_max_epu8(pixels, _mm_srli_epi32(pixels, 8)),
+                    _mm_srli_epi32(pixels, 16),
+                );
+                let level = _mm_and_si128(brightest, mask(0xFF));
+
+                match kernel {
+                    Kernel::Grayscale16 { swap } => {
+                        (_mm_or_si128(level, _mm_slli_epi32(level, 8)), swap)
+                    }
+                    _ => (level, false),
+                }
+            }
+        };
+
+        if swap {
+            _mm_or_si128(
+                _mm_and_si128(_mm_srli_epi32(value, 8), mask(0x00FF)),
+                _mm_and_si128(_mm_slli_epi32(value, 8), mask(0xFF00)),
+            )
+        } else {
+            value
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    fn encode_avx2(pixels: __m256i, kernel: Kernel) -> __m256i {
+        let mask = |value: i32| _mm256_set1_epi32(value);
+
+        let (value, swap) = match kernel {
+            Kernel::Rgb565 { swap } => (
+                _mm256_or_si256(
+                    _mm256_or_si256(
+                        _mm256_and_si256(_mm256_slli_epi32(pixels, 8), mask(0xF800)),
+                        _mm256_and_si256(_mm256_srli_epi32(pixels, 5), mask(0x07E0)),
+                    ),
+                    _mm256_and_si256(_mm256_srli_epi32(pixels, 19), mask(0x001F)),
+                ),
+                swap,
+            ),
+            Kernel::Bgr565 { swap } => (
+                _mm256_or_si256(
+                    _mm256_or_si256(
+                        _mm256_and_si256(_mm256_srli_epi32(pixels, 8), mask(0xF800)),
+                        _mm256_and_si256(_mm256_srli_epi32(pixels, 5), mask(0x07E0)),
+                    ),
+                    _mm256_and_si256(_mm256_srli_epi32(pixels, 3), mask(0x001F)),
+                ),
+                swap,
+            ),
+            Kernel::Grayscale8 | Kernel::Grayscale16 { .. } => {
+                let brightest = _mm256_max_epu8(
+                    _mm256_max_epu8(pixels, _mm256_srli_epi32(pixels, 8)),
+                    _mm256_srli_epi32(pixels, 16),
+                );
+                let level = _mm256_and_si256(brightest, mask(0xFF));
+
+                match kernel {
+                    Kernel::Grayscale16 { swap } => {
+                        (_mm256_or_si256(level, _mm256_slli_epi32(level, 8)), swap)
+                    }
+                    _ => (level, false),
+                }
+            }
+        };
+
+        if swap {
+            _mm256_or_si256(
+                _mm256_and_si256(_mm256_srli_epi32(value, 8), mask(0x00FF)),
+                _mm256_and_si256(_mm256_slli_epi32(value, 8), mask(0xFF00)),
+            )
+        } else {
+            value
+        }
+    }
+
+    /// Converts blocks of 8 pixels with SSE2.
+    ///
+    /// # Safety
+    ///
+    /// SSE2 must be supported by the running CPU.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn convert_sse2(
+        format: SliceFormat,
+        source: &[u8],
+        kernel: Kernel,
+        target: &mut [u8],
+    ) -> usize {
+        const PIXELS: usize = 8;
+
+        let source_block = PIXELS * format.bytes_per_pixel();
+        let target_block = PIXELS * kernel.bytes_per_pixel();
+
+        let mut converted = 0;
+
+        for (pixels, buf) in source
+            .chunks_exact(source_block)
+            .zip(target.chunks_exact_mut(target_block))
+        {
+            let [low, high] = match format {
+                // SAFETY: a block of RGBA8888 pixels holds 32 bytes
+                SliceFormat::RGBA8888 => unsafe {
+                    [
+                        _mm_loadu_si128(pixels.as_ptr().cast()),
+                        _mm_loadu_si128(pixels.as_ptr().add(16).cast()),
+                    ]
+                },
+                SliceFormat::RGB888 => [0, 4].map(|first| {
+                    _mm_setr_epi32(
+                        lane(&pixels[3 * first..]),
+                        lane(&pixels[3 * (first + 1)..]),
+                        lane(&pixels[3 * (first + 2)..]),
+                        lane(&pixels[3 * (first + 3)..]),
+                    )
+                }),
+            };
+
+            // Sign extended, so the signed saturation keeps the 16 bits values as is
+            let narrow = |lanes: __m128i| _mm_srai_epi32(_mm_slli_epi32(lanes, 16), 16);
+            let values = _mm_packs_epi32(
+                narrow(encode_sse2(low, kernel)),
+                narrow(encode_sse2(high, kernel)),
+            );
+
+            // SAFETY: a block of converted pixels holds 16 bytes, or 8 bytes in grayscale 8 bits
+            unsafe {
+                match kernel {
+                    Kernel::Grayscale8 => _mm_storel_epi64(
+                        buf.as_mut_ptr().cast(),
+                        _mm_packus_epi16(values, _mm_setzero_si128()),
+                    ),
+                    _ => _mm_storeu_si128(buf.as_mut_ptr().cast(), values),
+                }
+            }
+
+            converted += PIXELS;
+        }
+
+        converted
+    }
+
+    /// Converts blocks of 16 pixels with AVX2.
+    ///
+    /// # Safety
+    ///
+    /// AVX2 must be supported by the running CPU.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn convert_avx2(
+        format: SliceFormat,
+        source: &[u8],
+        kernel: Kernel,
+        target: &mut [u8],
+    ) -> usize {
+        const PIXELS: usize = 16;
+
+        let target_block = PIXELS * kernel.bytes_per_pixel();
+        let length = source.len() / format.bytes_per_pixel();
+
+        // A block of RGB888 pixels is read with two loads of 32 bytes at the offsets 0 and 24
+        let readable = match format {
+            SliceFormat::RGB888 => |converted: usize| 3 * converted + 56,
+            SliceFormat::RGBA8888 => |converted: usize| 4 * (converted + PIXELS),
+        };
+
+        // Moves the bytes 12 to 27 of the RGB888 pixels in the upper half, then spreads each
+        // half in four 32 bits lanes
+        let rgb_dwords = _mm256_setr_epi32(0, 1, 2, 3, 3, 4, 5, 6);
+        let rgb_bytes = _mm256_setr_epi8(
+            0, 1, 2, -1, 3, 4, 5, -1, 6, 7, 8, -1, 9, 10, 11, -1, 0, 1, 2, -1, 3, 4, 5, -1, 6, 7,
+            8, -1, 9, 10, 11, -1,
+        );
+
+        let mut converted = 0;
+
+        while converted + PIXELS <= length && readable(converted) <= source.len() {
+            let pixels = &source[converted * format.bytes_per_pixel()..];
+            let buf = &mut target[converted * kernel.bytes_per_pixel()..][..target_block];
+
+            // SAFETY: the loop condition keeps the loads in the source
+            let [low, high] = unsafe {
+                match format {
+                    SliceFormat::RGBA8888 => [
+                        _mm256_loadu_si256(pixels.as_ptr().cast()),
+                        _mm256_loadu_si256(pixels.as_ptr().add(32).cast()),
+                    ],
+                    SliceFormat::RGB888 => [0, 24].map(|offset| {
+                        let bytes = _mm256_loadu_si256(pixels.as_ptr().add(offset).cast());
+
+                        _mm256_shuffle_epi8(
+                            _mm256_permutevar8x32_epi32(bytes, rgb_dwords),
+                            rgb_bytes,
+                        )
+                    }),
+                }
+            };
+
+            // The packing works on each half, the quadwords are reordered afterwards
+            let values = _mm256_permute4x64_epi64(
+                _mm256_packus_epi32(encode_avx2(low, kernel), encode_avx2(high, kernel)),
+                0b11_01_10_00,
+            );
+
+            // SAFETY: a block of converted pixels holds 32 bytes, or 16 bytes in grayscale 8 bits
+            unsafe {
+                match kernel {
+                    Kernel::Grayscale8 => _mm_storeu_si128(
+                        buf.as_mut_ptr().cast(),
+                        _mm256_castsi256_si128(_mm256_permute4x64_epi64(
+                            _mm256_packus_epi16(values, values),
+                            0b00_00_10_00,
+                        )),
+                    ),
+                    _ => _mm256_storeu_si256(buf.as_mut_ptr().cast(), values),
+                }
+            }
+
+            converted += PIXELS;
+        }
+
+        converted
+    }
+}
+
+/// NEON backend, the pixels being deinterleaved by the loads.
+#[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+mod neon {
+    use std::arch::aarch64::*;
+
+    use super::{Kernel, SliceFormat};
+
+    /// Converts blocks of 16 pixels with NEON.
+    ///
+    /// # Safety
+    ///
+    /// NEON must be supported by the running CPU.
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn convert(
+        format: SliceFormat,
+        source: &[u8],
+        kernel: Kernel,
+        target: &mut [u8],
+    ) -> usize {
+        const PIXELS: usize = 16;
+
+        let source_block = PIXELS * format.bytes_per_pixel();
+        let target_block = PIXELS * kernel.bytes_per_pixel();
+
+        let mut converted = 0;
+
+        for (pixels, buf) in source
+            .chunks_exact(source_block)
+            .zip(target.chunks_exact_mut(target_block))
+        {
+            // SAFETY: the block holds 16 pixels
+            let [red, green, blue] = unsafe {
+                match format {
+                    SliceFormat::RGB888 => {
+                        let channels = vld3q_u8(pixels.as_ptr());
+
+                        [channels.0, channels.1, channels.2]
+                    }
+                    SliceFormat::RGBA8888 => {
+                        let channels = vld4q_u8(pixels.as_ptr());
+
+                        [channels.0, channels.1, channels.2]
+                    }
+                }
+            };
+
+            let (values, swap) = match kernel {
+                Kernel::Rgb565 { swap } => (pack_565(red, green, blue), swap),
+                Kernel::Bgr565 { swap } => (pack_565(blue, green, red), swap),
+                Kernel::Grayscale8 => {
+                    // SAFETY: a block of converted pixels holds 16 bytes
+                    unsafe {
+                        vst1q_u8(buf.as_mut_ptr(), vmaxq_u8(vmaxq_u8(red, green), blue));
+                    }
+
+                    converted += PIXELS;
+
+                    continue;
+                }
+                Kernel::Grayscale16 { swap } => {
+                    let brightest = vmaxq_u8(vmaxq_u8(red, green), blue);
+
+                    // Each level is repeated in both bytes
+                    (
+                        [
+                            vzip1q_u8(brightest, brightest),
+                            vzip2q_u8(brightest, brightest),
+                        ],
+                        swap,
+                    )
+                }
+            };
+
+            for (half, value) in values.into_iter().enumerate() {
+                let bytes = if swap { vrev16q_u8(value) } else { value };
+
+                // SAFETY: a block of converted pixels holds 32 bytes
+                unsafe {
+                    vst1q_u8(buf.as_mut_ptr().add(16 * half), bytes);
+                }
+            }
+
+            converted += PIXELS;
+        }
+
+        converted
+    }
+
+    /// Packs the truncated components in the 565 layout, the first one being in the most
+    /// significant bits. Gives the little endian bytes of the 16 pixels.
+    #[target_feature(enable = "neon")]
+    fn pack_565(first: uint8x16_t, second: uint8x16_t, third: uint8x16_t) -> [uint8x16_t; 2] {
+        let pack = |first: uint8x8_t, second: uint8x8_t, third: uint8x8_t| {
+            let value = vorrq_u16(
+                vorrq_u16(
+                    vshlq_n_u16::<8>(vmovl_u8(vand_u8(first, vdup_n_u8(0xF8)))),
+                    vshlq_n_u16::<3>(vmovl_u8(vand_u8(second, vdup_n_u8(0xFC)))),
+                ),
+                vmovl_u8(vshr_n_u8::<3>(third)),
+            );
+
+            vreinterpretq_u8_u16(value)
+        };
+
+        [
+            pack(vget_low_u8(first), vget_low_u8(second), vget_low_u8(third)),
+            pack(
+                vget_high_u8(first),
+                vget_high_u8(second),
+                vget_high_u8(third),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::{convert_slice, convert_with, Backend, SliceFormat};
+    use crate::{Color, ColorMode, CoreError, SubpixelPacking};
+
+    const MODES: [ColorMode; 7] = [
+        ColorMode::RGB565LE,
+        ColorMode::RGB565BE,
+        ColorMode::BGR565LE,
+        ColorMode::BGR565BE,
+        ColorMode::Grayscale8Bit,
+        ColorMode::Grayscale16BitLE,
+        ColorMode::Grayscale16BitBE,
+    ];
+
+    /// Gives the backends supported by the running CPU.
+    fn backends() -> Vec<Backend> {
+        let mut backends = vec![Backend::Portable];
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("sse2") {
+                backends.push(Backend::Sse2);
+            }
+
+            if is_x86_feature_detected!("avx2") {
+                backends.push(Backend::Avx2);
+            }
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_endian = "little"))]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                backends.push(Backend::Neon);
+            }
+        }
+
+        backends
+    }
+
+    /// Gives pixels covering every value of each component, in varied combinations.
+    fn pixels(format: SliceFormat, length: usize) -> Vec<u8> {
+        let mut state = 0x2545F491u32;
+
+        (0..length * format.bytes_per_pixel())
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+
+                if i < 256 * format.bytes_per_pixel() {
+                    (i / format.bytes_per_pixel()) as u8
+                } else {
+                    state as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bit_exact() {
+        for format in [SliceFormat::RGB888, SliceFormat::RGBA8888] {
+            // Lengths covering the whole blocks and the remaining pixels of every backend
+            for length in [0, 1, 7, 8, 15, 16, 17, 31, 33, 1000] {
+                let source = pixels(format, length);
+
+                for mode in MODES {
+                    let bytes = mode
+                        .byte_size()
+                        .buffer_length(1, 1, SubpixelPacking::RowPadded);
+                    let size = length * bytes;
+                    let mut expected = vec![0u8; size];
+
+                    for (pixel, buf) in source
+                        .chunks_exact(format.bytes_per_pixel())
+                        .zip(expected.chunks_exact_mut(bytes))
+                    {
+                        let color = [pixel[0], pixel[1], pixel[2]];
+
+                        match mode {
+                            ColorMode::RGB565LE => color.fill_rgb565le(buf),
+                            ColorMode::RGB565BE => color.fill_rgb565be(buf),
+                            ColorMode::BGR565LE => color.fill_bgr565le(buf),
+                            ColorMode::BGR565BE => color.fill_bgr565be(buf),
+                            ColorMode::Grayscale8Bit => color.fill_grayscale_8bit(buf),
+                            ColorMode::Grayscale16BitLE => color.fill_grayscale_16bit_le(buf),
+                            _ => color.fill_grayscale_16bit_be(buf),
+                        }
+                        .unwrap();
+                    }
+
+                    for backend in backends() {
+                        let mut buffer = vec![0xAAu8; size];
+
+                        convert_with(backend, format, &source, mode, &mut buffer).unwrap();
+
+                        assert_eq!(buffer, expected, "{backend:?} {format:?} {mode:?} {length}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_slice() {
+        let mut buffer = [0u8; 4];
+
+        convert_slice(
+            SliceFormat::RGBA8888,
+            &[0xFF, 0x80, 0x08, 0x00, 0x12, 0x34, 0x56, 0xFF],
+            ColorMode::RGB565BE,
+            &mut buffer,
+        )
+        .unwrap();
+
+        assert_eq!(buffer, [0b11111_100, 0b000_00001, 0b00010_001, 0b101_01010]);
+
+        assert_eq!(
+            convert_slice(SliceFormat::RGB888, &[0; 6], ColorMode::RGB, &mut [0; 6]),
+            Err(CoreError::UnsupportedColorMode {
+                mode: ColorMode::RGB
+            })
+        );
+        assert_eq!(
+            convert_slice(
+                SliceFormat::RGB888,
+                &[0; 7],
+                ColorMode::RGB565LE,
+                &mut [0; 4]
+            ),
+            Err(CoreError::BufferSizeError {
+                given: 7,
+                expected: 6
+            })
+        );
+        assert_eq!(
+            convert_slice(
+                SliceFormat::RGBA8888,
+                &[0; 8],
+                ColorMode::Grayscale8Bit,
+                &mut [0; 3]
+            ),
+            Err(CoreError::BufferSizeError {
+                given: 3,
+                expected: 2
+            })
+        );
+    }
+}
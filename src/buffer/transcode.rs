@@ -0,0 +1,445 @@
+use super::{
+    convert_slice, simd, Buffer, ColorMode, Decoder, FillTarget, SliceFormat, SubpixelPacking,
+    TargetLayout,
+};
+use crate::{CoreError, CoreResult};
+
+/// Conversion of the rows of a color mode to another one which does not need to decode the
+/// colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FastPath {
+    /// Same color mode, the rows are copied.
+    Copy,
+    /// Components of 8 bits moved to other positions, the unused byte of the target being set to
+    /// `0xFF`.
+    Channels { source: Channels, target: Channels },
+    /// Pixels of 16 bits whose byte order changes or whose red and blue fields are swapped.
+    Words {
+        source_big_endian: bool,
+        target_big_endian: bool,
+        swap_fields: bool,
+    },
+    /// RGB888 or RGBA8888 converted by [`convert_slice`].
+    Slice {
+        format: SliceFormat,
+        mode: ColorMode,
+    },
+}
+
+/// Positions of the red, green and blue components of the pixels storing them on 8 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Channels {
+    bytes_per_pixel: usize,
+    positions: [usize; 3],
+}
+
+/// Kind of the pixels of 16 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Word {
+    Rgb565,
+    Bgr565,
+    Grayscale16,
+}
+
+impl Channels {
+    const fn from_mode(mode: ColorMode) -> Option<Channels> {
+        let (bytes_per_pixel, positions) = match mode {
+            ColorMode::RGB => (3, [0, 1, 2]),
+            ColorMode::BGR => (3, [2, 1, 0]),
+            ColorMode::XRGB8888 => (4, [1, 2, 3]),
+            ColorMode::RGBA8888 => (4, [0, 1, 2]),
+            _ => return None,
+        };
+
+        Some(Channels {
+            bytes_per_pixel,
+            positions,
+        })
+    }
+}
+
+impl Word {
+    /// Gives the kind of the pixels and whether they are stored in big endian.
+    const fn from_mode(mode: ColorMode) -> Option<(Word, bool)> {
+        match mode {
+            ColorMode::RGB565LE => Some((Word::Rgb565, false)),
+            ColorMode::RGB565BE => Some((Word::Rgb565, true)),
+            ColorMode::BGR565LE => Some((Word::Bgr565, false)),
+            ColorMode::BGR565BE => Some((Word::Bgr565, true)),
+            ColorMode::Grayscale16BitLE => Some((Word::Grayscale16, false)),
+            ColorMode::Grayscale16BitBE => Some((Word::Grayscale16, true)),
+            _ => None,
+        }
+    }
+}
+
+impl FastPath {
+    fn new(source: ColorMode, target: ColorMode) -> Option<FastPath> {
+        if source == target {
+            return Some(FastPath::Copy);
+        }
+
+        if let (Some(source), Some(target)) =
+            (Channels::from_mode(source), Channels::from_mode(target))
+        {
+            return Some(FastPath::Channels { source, target });
+        }
+
+        if let (Some((source, source_big_endian)), Some((target, target_big_endian))) =
+            (Word::from_mode(source), Word::from_mode(target))
+        {
+            let swap_fields = match (source, target) {
+                (Word::Grayscale16, Word::Grayscale16) => false,
+                (Word::Grayscale16, _) | (_, Word::Grayscale16) => return None,
+                _ => source != target,
+            };
+
+            return Some(FastPath::Words {
+                source_big_endian,
+                target_big_endian,
+                swap_fields,
+            });
+        }
+
+        let format = match source {
+            ColorMode::RGB => SliceFormat::RGB888,
+            ColorMode::RGBA8888 => SliceFormat::RGBA8888,
+            _ => return None,
+        };
+
+        simd::supports(target).then_some(FastPath::Slice {
+            format,
+            mode: target,
+        })
+    }
+
+    fn convert_row(&self, source: &[u8], target: &mut [u8]) -> CoreResult<()> {
+        match self {
+            FastPath::Copy => target.copy_from_slice(source),
+            FastPath::Channels {
+                source: from,
+                target: to,
+            } => {
+                for (pixel, buf) in source
+                    .chunks_exact(from.bytes_per_pixel)
+                    .zip(target.chunks_exact_mut(to.bytes_per_pixel))
+                {
+                    buf.fill(0xFF);
+
+                    for (from, to) in from.positions.into_iter().zip(to.positions) {
+                        buf[to] = pixel[from];
+                    }
+                }
+            }
+            FastPath::Words {
+                source_big_endian,
+                target_big_endian,
+                swap_fields,
+            } => {
+                for (pixel, buf) in source.chunks_exact(2).zip(target.chunks_exact_mut(2)) {
+                    let pixel = [pixel[0], pixel[1]];
+                    let mut word = if *source_big_endian {
+                        u16::from_be_bytes(pixel)
+                    } else {
+                        u16::from_le_bytes(pixel)
+                    };
+
+                    if *swap_fields {
+                        word = word & 0x07E0 | word >> 11 | word << 11;
+                    }
+
+                    buf.copy_from_slice(&if *target_big_endian {
+                        word.to_be_bytes()
+                    } else {
+                        word.to_le_bytes()
+                    });
+                }
+            }
+            FastPath::Slice { format, mode } => convert_slice(*format, source, *mode, target)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts an image of the given dimensions from a color mode to another one, both buffers
+/// exactly holding its rows.
+///
+/// See [`transcode_strided`].
+pub fn transcode(
+    source_mode: ColorMode,
+    source: &[u8],
+    target_mode: ColorMode,
+    target: &mut [u8],
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    let packing = SubpixelPacking::RowPadded;
+    let source_length = source_mode.byte_size().buffer_length(width, 1, packing);
+    let target_length = target_mode.byte_size().buffer_length(width, 1, packing);
+
+    CoreError::check_length(source, source_length * height as usize)?;
+    CoreError::check_length(target, target_length * height as usize)?;
+
+    transcode_strided(
+        source_mode,
+        source,
+        TargetLayout::new(source_length),
+        target_mode,
+        target,
+        TargetLayout::new(target_length),
+        width,
+        height,
+    )
+}
+
+/// Converts an image of the given dimensions from a color mode to another one, the rows of both
+/// buffers being placed according to their layout.
+///
+/// Each row begins on a new byte. The copies, the byte swaps, the moves of the components and the
+/// conversions of [`convert_slice`] are done row by row, the other color modes being decoded with
+/// a [`Decoder`] then encoded with the default [`FillOptions`](super::FillOptions). The indexed
+/// color modes need a palette, so they can only be copied. Decode them with a [`Decoder`] holding
+/// their palette instead.
+#[allow(clippy::too_many_arguments)]
+pub fn transcode_strided(
+    source_mode: ColorMode,
+    source: &[u8],
+    source_layout: TargetLayout,
+    target_mode: ColorMode,
+    target: &mut [u8],
+    target_layout: TargetLayout,
+    width: u16,
+    height: u16,
+) -> CoreResult<()> {
+    let packing = SubpixelPacking::RowPadded;
+    let source_length = source_mode.byte_size().buffer_length(width, 1, packing);
+    let target_length = target_mode.byte_size().buffer_length(width, 1, packing);
+
+    let Some(fast_path) = FastPath::new(source_mode, target_mode) else {
+        let frame =
+            Decoder::new(source_mode).decode_strided(source, source_layout, width, height)?;
+
+        return (&frame).fill_strided(target_mode, target, target_layout, 0, 0, width, height);
+    };
+
+    let rows = source_layout.rows(source, source_length, height as usize)?;
+    let buffers =
+        FillTarget::with_layout(target, target_layout).rows(target_length, height as usize)?;
+
+    for (row, buf) in rows.zip(buffers) {
+        fast_path.convert_row(row, buf)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transcode, transcode_strided, FastPath};
+    use crate::{Buffer, ColorMode, CoreError, Decoder, PixelSlice, TargetLayout};
+
+    const MODES: [ColorMode; 21] = [
+        ColorMode::RGB,
+        ColorMode::BGR,
+        ColorMode::XRGB8888,
+        ColorMode::RGBA8888,
+        ColorMode::RGB666,
+        ColorMode::RGB444,
+        ColorMode::RGB332,
+        ColorMode::RGB565LE,
+        ColorMode::RGB565BE,
+        ColorMode::BGR565LE,
+        ColorMode::BGR565BE,
+        ColorMode::Grayscale1Bit,
+        ColorMode::Grayscale2Bit,
+        ColorMode::Grayscale4Bit,
+        ColorMode::Grayscale8Bit,
+        ColorMode::Grayscale16BitLE,
+        ColorMode::Grayscale16BitBE,
+        ColorMode::Indexed1Bit,
+        ColorMode::Indexed2Bit,
+        ColorMode::Indexed4Bit,
+        ColorMode::Indexed8Bit,
+    ];
+
+    fn length(mode: ColorMode, width: u16, height: u16) -> usize {
+        mode.byte_size()
+            .buffer_length(width, height, Default::default())
+    }
+
+    #[test]
+    fn test_transcode() {
+        let pixels = (0..35u32)
+            .map(|i| [(i * 37) as u8, (i * 91 + 13) as u8, (i * 53 + 200) as u8])
+            .collect::<Vec<_>>();
+        let source = PixelSlice::new(&pixels, 7);
+
+        for source_mode in MODES {
+            let mut data = vec![0u8; length(source_mode, 7, 5)];
+
+            if source.fill(source_mode, &mut data, 0, 0, 7, 5).is_err() {
+                // The indexed color modes can only be copied
+                let mut target = vec![0u8; length(ColorMode::RGB, 7, 5)];
+
+                assert_eq!(
+                    transcode(source_mode, &data, ColorMode::RGB, &mut target, 7, 5),
+                    Err(CoreError::MissingPalette)
+                );
+
+                continue;
+            }
+
+            for target_mode in MODES {
+                let mut target = vec![0u8; length(target_mode, 7, 5)];
+                let result = transcode(source_mode, &data, target_mode, &mut target, 7, 5);
+
+                // Decoding then encoding gives the expected bytes
+                let mut expected = vec![0u8; target.len()];
+                let frame = Decoder::new(source_mode).decode(&data, 7, 5).unwrap();
+                let expected_result = (&frame).fill(target_mode, &mut expected, 0, 0, 7, 5);
+
+                assert_eq!(result, expected_result, "{source_mode:?} {target_mode:?}");
+                assert_eq!(target, expected, "{source_mode:?} {target_mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_fast_paths() {
+        assert_eq!(
+            FastPath::new(ColorMode::Indexed4Bit, ColorMode::Indexed4Bit),
+            Some(FastPath::Copy)
+        );
+        assert_eq!(
+            FastPath::new(ColorMode::RGB565LE, ColorMode::BGR565BE),
+            Some(FastPath::Words {
+                source_big_endian: false,
+                target_big_endian: true,
+                swap_fields: true
+            })
+        );
+        assert_eq!(
+            FastPath::new(ColorMode::Grayscale16BitBE, ColorMode::Grayscale16BitLE),
+            Some(FastPath::Words {
+                source_big_endian: true,
+                target_big_endian: false,
+                swap_fields: false
+            })
+        );
+        assert_eq!(
+            FastPath::new(ColorMode::Grayscale16BitBE, ColorMode::RGB565BE),
+            None
+        );
+        assert!(matches!(
+            FastPath::new(ColorMode::BGR, ColorMode::XRGB8888),
+            Some(FastPath::Channels { .. })
+        ));
+        assert!(matches!(
+            FastPath::new(ColorMode::RGBA8888, ColorMode::Grayscale8Bit),
+            Some(FastPath::Slice { .. })
+        ));
+        assert_eq!(FastPath::new(ColorMode::BGR, ColorMode::RGB332), None);
+
+        // Red, green and blue as RGB565 little endian, then BGR565 big endian
+        let mut target = [0u8; 6];
+
+        transcode(
+            ColorMode::RGB565LE,
+            &[0x00, 0xF8, 0xE0, 0x07, 0x1F, 0x00],
+            ColorMode::BGR565BE,
+            &mut target,
+            3,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(target, [0x00, 0x1F, 0x07, 0xE0, 0xF8, 0x00]);
+    }
+
+    #[test]
+    fn test_transcode_strided() {
+        let source = [0xAA, 0x12, 0x34, 0xAA, 0x56, 0x78];
+        let mut target = [0u8; 9];
+
+        transcode_strided(
+            ColorMode::Grayscale16BitBE,
+            &source,
+            TargetLayout::new(3).with_offset(1),
+            ColorMode::Grayscale16BitLE,
+            &mut target,
+            TargetLayout::new(4).with_offset(2),
+            1,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(target, [0, 0, 0x34, 0x12, 0, 0, 0x78, 0x56, 0]);
+
+        transcode_strided(
+            ColorMode::Grayscale16BitBE,
+            &source,
+            TargetLayout::new(3).with_offset(1),
+            ColorMode::Grayscale8Bit,
+            &mut target,
+            TargetLayout::new(4),
+            1,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(&target[..5], [0x12, 0x00, 0x34, 0x12, 0x56]);
+        assert_eq!(
+            transcode_strided(
+                ColorMode::Grayscale16BitBE,
+                &source,
+                TargetLayout::new(1),
+                ColorMode::Grayscale8Bit,
+                &mut target,
+                TargetLayout::new(4),
+                1,
+                2,
+            ),
+            Err(CoreError::InvalidStride {
+                stride: 1,
+                row_length: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_transcode_errors() {
+        assert_eq!(
+            transcode(ColorMode::RGB, &[0; 5], ColorMode::BGR, &mut [0; 6], 2, 1),
+            Err(CoreError::BufferSizeError {
+                given: 5,
+                expected: 6
+            })
+        );
+        assert_eq!(
+            transcode(
+                ColorMode::RGB,
+                &[0; 6],
+                ColorMode::RGB332,
+                &mut [0; 3],
+                2,
+                1
+            ),
+            Err(CoreError::BufferSizeError {
+                given: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            transcode(
+                ColorMode::RGB,
+                &[0; 3],
+                ColorMode::Indexed8Bit,
+                &mut [0; 1],
+                1,
+                1
+            ),
+            Err(CoreError::MissingPalette)
+        );
+    }
+}
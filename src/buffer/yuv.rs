@@ -0,0 +1,477 @@
+use super::frame_buffer::check_frame_area;
+use super::IntoPixelIter;
+use crate::{CoreError, CoreResult};
+
+/// Storage of the planes of a YUV frame.
+///
+/// The chroma is subsampled by two horizontally, and vertically for the 4:2:0 layouts. An odd
+/// width or height is rounded up for the chroma.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YuvLayout {
+    /// 4:2:0 planar: the Y plane, then the U plane, then the V plane.
+    I420,
+    /// 4:2:0 semi-planar: the Y plane, then a plane of interleaved U and V samples.
+    NV12,
+    /// 4:2:2 packed: each pair of pixels of a row is stored as `Y0 U Y1 V`.
+    YUYV,
+}
+
+/// Coefficients used to convert the YUV samples to RGB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YuvMatrix {
+    /// ITU-R BT.601, used by the standard definition video.
+    #[default]
+    Bt601,
+    /// ITU-R BT.709, used by the high definition video.
+    Bt709,
+}
+
+/// Range of the YUV samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum YuvRange {
+    /// Luma between 16 and 235, chroma between 16 and 240, as most video.
+    #[default]
+    Limited,
+    /// Luma and chroma using the 256 values, as JPEG.
+    Full,
+}
+
+/// Frame of YUV samples, converted to RGB pixels.
+///
+/// The chroma of a pixel is the sample of the block holding it, without interpolation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct YuvFrame<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    layout: YuvLayout,
+    matrix: YuvMatrix,
+    range: YuvRange,
+}
+
+/// Pixels of an area of a [`YuvFrame`], row after row.
+#[derive(Clone, Debug)]
+pub struct YuvFrameIter<'a> {
+    frame: YuvFrame<'a>,
+    coefficients: Coefficients,
+    left: u16,
+    right: u16,
+    bottom: u16,
+    x: u16,
+    y: u16,
+}
+
+/// Conversion of the samples in 16.16 fixed point.
+#[derive(Clone, Copy, Debug)]
+struct Coefficients {
+    luma_offset: i32,
+    luma: i32,
+    red_v: i32,
+    green_u: i32,
+    green_v: i32,
+    blue_u: i32,
+}
+
+impl YuvMatrix {
+    /// Gives the weights of the red and blue components in the luma.
+    const fn weights(&self) -> (f64, f64) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+impl Coefficients {
+    const ONE: f64 = 65536.0;
+
+    fn new(matrix: YuvMatrix, range: YuvRange) -> Coefficients {
+        let (kr, kb) = matrix.weights();
+        let kg = 1.0 - kr - kb;
+
+        let (luma_offset, luma_scale, chroma_scale) = match range {
+            YuvRange::Limited => (16, 255.0 / 219.0, 255.0 / 224.0),
+            YuvRange::Full => (0, 1.0, 1.0),
+        };
+
+        let fixed = |value: f64| (value * Self::ONE).round() as i32;
+
+        Coefficients {
+            luma_offset,
+            luma: fixed(luma_scale),
+            red_v: fixed(2.0 * (1.0 - kr) * chroma_scale),
+            green_u: fixed(-2.0 * kb * (1.0 - kb) / kg * chroma_scale),
+            green_v: fixed(-2.0 * kr * (1.0 - kr) / kg * chroma_scale),
+            blue_u: fixed(2.0 * (1.0 - kb) * chroma_scale),
+        }
+    }
+
+    fn rgb(&self, [y, u, v]: [u8; 3]) -> [u8; 3] {
+        let luma = (y as i32 - self.luma_offset) * self.luma;
+        let u = u as i32 - 128;
+        let v = v as i32 - 128;
+
+        let component = |value: i32| ((value + 0x8000) >> 16).clamp(0, 255) as u8;
+
+        [
+            component(luma + self.red_v * v),
+            component(luma + self.green_u * u + self.green_v * v),
+            component(luma + self.blue_u * u),
+        ]
+    }
+}
+
+impl YuvLayout {
+    /// Gives the number of bytes of a frame.
+    pub const fn frame_length(&self, width: u16, height: u16) -> usize {
+        let luma = width as usize * height as usize;
+        let chroma_width = (width as usize).div_ceil(2);
+
+        match self {
+            YuvLayout::I420 | YuvLayout::NV12 => {
+                luma + 2 * chroma_width * (height as usize).div_ceil(2)
+            }
+            YuvLayout::YUYV => 4 * chroma_width * height as usize,
+        }
+    }
+}
+
+impl<'a> YuvFrame<'a> {
+    /// Creates a frame using the BT.601 matrix and the limited range.
+    pub fn new(
+        data: &'a [u8],
+        width: u16,
+        height: u16,
+        layout: YuvLayout,
+    ) -> CoreResult<YuvFrame<'a>> {
+        CoreError::check_length(data, layout.frame_length(width, height))?;
+
+        Ok(YuvFrame {
+            data,
+            width,
+            height,
+            layout,
+            matrix: YuvMatrix::Bt601,
+            range: YuvRange::Limited,
+        })
+    }
+
+    pub const fn with_matrix(mut self, matrix: YuvMatrix) -> YuvFrame<'a> {
+        self.matrix = matrix;
+
+        self
+    }
+
+    pub const fn with_range(mut self, range: YuvRange) -> YuvFrame<'a> {
+        self.range = range;
+
+        self
+    }
+
+    pub const fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub const fn layout(&self) -> YuvLayout {
+        self.layout
+    }
+
+    pub const fn matrix(&self) -> YuvMatrix {
+        self.matrix
+    }
+
+    pub const fn range(&self) -> YuvRange {
+        self.range
+    }
+
+    /// Gives the Y, U and V samples of a pixel inside the frame.
+    fn samples(&self, x: u16, y: u16) -> [u8; 3] {
+        let (x, y) = (x as usize, y as usize);
+        let width = self.width as usize;
+        let luma = width * self.height as usize;
+        let chroma_width = width.div_ceil(2);
+        let chroma = (y / 2) * chroma_width + x / 2;
+
+        match self.layout {
+            YuvLayout::I420 => {
+                let plane = chroma_width * (self.height as usize).div_ceil(2);
+
+                [
+                    self.data[y * width + x],
+                    self.data[luma + chroma],
+                    self.data[luma + plane + chroma],
+                ]
+            }
+            YuvLayout::NV12 => [
+                self.data[y * width + x],
+                self.data[luma + 2 * chroma],
+                self.data[luma + 2 * chroma + 1],
+            ],
+            YuvLayout::YUYV => {
+                let pair = 4 * (y * chroma_width + x / 2);
+
+                [
+                    self.data[pair + 2 * (x % 2)],
+                    self.data[pair + 1],
+                    self.data[pair + 3],
+                ]
+            }
+        }
+    }
+}
+
+impl<'a> IntoPixelIter for YuvFrame<'a> {
+    type IntoIter = YuvFrameIter<'a>;
+    type Item = [u8; 3];
+
+    /// Gives the RGB pixels of the area, or none when the area goes past the frame.
+    fn into_pixel_iter(self, x: u16, y: u16, width: u16, height: u16) -> Self::IntoIter {
+        let (right, bottom) = match self.check_area(x, y, width, height) {
+            Ok(()) => (x + width, y + height),
+            Err(_) => (x, y),
+        };
+
+        YuvFrameIter {
+            frame: self,
+            coefficients: Coefficients::new(self.matrix, self.range),
+            left: x,
+            right,
+            bottom,
+            x,
+            y,
+        }
+    }
+
+    fn check_area(&self, x: u16, y: u16, width: u16, height: u16) -> CoreResult<()> {
+        check_frame_area(
+            self.width as usize,
+            self.height as usize,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+}
+
+impl<'a> Iterator for YuvFrameIter<'a> {
+    type Item = [u8; 3];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == self.right || self.y >= self.bottom {
+            return None;
+        }
+
+        let samples = self.frame.samples(self.x, self.y);
+
+        self.x += 1;
+
+        if self.x == self.right {
+            self.x = self.left;
+            self.y += 1;
+        }
+
+        Some(self.coefficients.rgb(samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{YuvFrame, YuvLayout, YuvMatrix, YuvRange};
+    use crate::{Buffer, CoreError, IntoPixelIter};
+
+    /// Converts the samples with the floating point formulas.
+    fn reference([y, u, v]: [u8; 3], matrix: YuvMatrix, range: YuvRange) -> [f64; 3] {
+        let (kr, kb) = match matrix {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        };
+        let (y, u, v) = match range {
+            YuvRange::Limited => (
+                (y as f64 - 16.0) / 219.0,
+                (u as f64 - 128.0) / 224.0,
+                (v as f64 - 128.0) / 224.0,
+            ),
+            YuvRange::Full => (
+                y as f64 / 255.0,
+                (u as f64 - 128.0) / 255.0,
+                (v as f64 - 128.0) / 255.0,
+            ),
+        };
+
+        let red = y + 2.0 * (1.0 - kr) * v;
+        let blue = y + 2.0 * (1.0 - kb) * u;
+        let green = (y - kr * red - kb * blue) / (1.0 - kr - kb);
+
+        [red, green, blue].map(|component| (component * 255.0).clamp(0.0, 255.0))
+    }
+
+    fn convert(samples: [u8; 3], matrix: YuvMatrix, range: YuvRange) -> [u8; 3] {
+        let data = [samples[0], samples[1], samples[0], samples[2]];
+        let frame = YuvFrame::new(&data, 2, 1, YuvLayout::YUYV)
+            .unwrap()
+            .with_matrix(matrix)
+            .with_range(range);
+
+        frame.into_pixel_iter(0, 0, 1, 1).next().unwrap()
+    }
+
+    #[test]
+    fn test_conversion() {
+        for matrix in [YuvMatrix::Bt601, YuvMatrix::Bt709] {
+            assert_eq!(
+                convert([16, 128, 128], matrix, YuvRange::Limited),
+                [0, 0, 0]
+            );
+            assert_eq!(
+                convert([235, 128, 128], matrix, YuvRange::Limited),
+                [255; 3]
+            );
+            assert_eq!(convert([0, 128, 128], matrix, YuvRange::Full), [0, 0, 0]);
+            assert_eq!(convert([0x80, 128, 128], matrix, YuvRange::Full), [0x80; 3]);
+            assert_eq!(convert([255, 128, 128], matrix, YuvRange::Full), [255; 3]);
+
+            for range in [YuvRange::Limited, YuvRange::Full] {
+                for samples in (0..=255u8)
+                    .step_by(15)
+                    .flat_map(|y| [[y, 0, 255], [y, 255, 0], [y, 40, 200], [y, 128, 90]])
+                {
+                    let expected = reference(samples, matrix, range);
+                    let rgb = convert(samples, matrix, range);
+
+                    for (component, expected) in rgb.into_iter().zip(expected) {
+                        assert!(
+                            (component as f64 - expected).abs() <= 0.5 + 1e-3,
+                            "{samples:?} {matrix:?} {range:?}: {rgb:?} {expected:?}"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Primary colors of the BT.601 limited range, the red one losing a level to the rounding
+        // of its samples
+        assert_eq!(
+            convert([81, 90, 240], YuvMatrix::Bt601, YuvRange::Limited),
+            [254, 0, 0]
+        );
+        assert_eq!(
+            convert([41, 240, 110], YuvMatrix::Bt601, YuvRange::Limited),
+            [0, 0, 255]
+        );
+    }
+
+    /// Gives the luma of each pixel of a 3x3 frame and the chroma of each block, the U and V
+    /// samples of a block being equal.
+    fn frame(layout: YuvLayout) -> Vec<u8> {
+        let luma = [16, 30, 50, 70, 90, 110, 130, 150, 170];
+
+        match layout {
+            YuvLayout::I420 => [&luma[..], &[100, 110, 120, 130], &[100, 110, 120, 130]].concat(),
+            YuvLayout::NV12 => [&luma[..], &[100, 100, 110, 110, 120, 120, 130, 130]].concat(),
+            YuvLayout::YUYV => vec![
+                16, 100, 30, 100, 50, 110, 0, 110, //
+                70, 100, 90, 100, 110, 110, 0, 110, //
+                130, 120, 150, 120, 170, 130, 0, 130,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_layouts() {
+        let expected = [
+            [16, 100],
+            [30, 100],
+            [50, 110],
+            [70, 100],
+            [90, 100],
+            [110, 110],
+            [130, 120],
+            [150, 120],
+            [170, 130],
+        ];
+
+        for layout in [YuvLayout::I420, YuvLayout::NV12] {
+            let data = frame(layout);
+            let frame = YuvFrame::new(&data, 3, 3, layout).unwrap();
+
+            for (i, [y, chroma]) in expected.into_iter().enumerate() {
+                assert_eq!(
+                    frame.samples(i as u16 % 3, i as u16 / 3),
+                    [y, chroma, chroma],
+                    "{layout:?}"
+                );
+            }
+        }
+
+        // The chroma of the YUYV layout is only subsampled horizontally
+        let data = frame(YuvLayout::YUYV);
+        let frame = YuvFrame::new(&data, 3, 3, YuvLayout::YUYV).unwrap();
+
+        assert_eq!(frame.samples(1, 0), [30, 100, 100]);
+        assert_eq!(frame.samples(2, 1), [110, 110, 110]);
+        assert_eq!(frame.samples(1, 2), [150, 120, 120]);
+    }
+
+    #[test]
+    fn test_frame_length() {
+        assert_eq!(YuvLayout::I420.frame_length(4, 2), 12);
+        assert_eq!(YuvLayout::NV12.frame_length(3, 3), 17);
+        assert_eq!(YuvLayout::YUYV.frame_length(3, 2), 16);
+
+        assert_eq!(
+            YuvFrame::new(&[0; 16], 3, 3, YuvLayout::NV12),
+            Err(CoreError::BufferSizeError {
+                given: 16,
+                expected: 17
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let data = frame(YuvLayout::NV12);
+        let frame = YuvFrame::new(&data, 3, 3, YuvLayout::NV12)
+            .unwrap()
+            .with_range(YuvRange::Full);
+
+        // Gray chroma, the luma is given as is
+        let data = [
+            [16, 30, 50, 70, 90, 110, 130, 150, 170].as_slice(),
+            &[128; 8],
+        ]
+        .concat();
+        let gray = YuvFrame::new(&data, 3, 3, YuvLayout::NV12)
+            .unwrap()
+            .with_range(YuvRange::Full);
+        let mut buffer = [0u8; 4];
+
+        gray.fill_grayscale_8bit(&mut buffer, 1, 1, 2, 2).unwrap();
+        assert_eq!(buffer, [90, 110, 150, 170]);
+
+        assert_eq!(frame.into_pixel_iter(2, 2, 1, 1).count(), 1);
+        assert_eq!(frame.into_pixel_iter(0, 1, 3, 2).count(), 6);
+
+        // Going past the frame
+        assert_eq!(frame.into_pixel_iter(2, 2, 5, 5).count(), 0);
+        assert_eq!(frame.into_pixel_iter(3, 0, 5, 5).count(), 0);
+        assert_eq!(frame.into_pixel_iter(0, 1, 3, 9).count(), 0);
+
+        let mut buffer = [0xEEu8; 4];
+
+        assert_eq!(
+            gray.fill_grayscale_8bit(&mut buffer, 2, 1, 2, 2),
+            Err(CoreError::AreaOutOfBounds {
+                x: 2,
+                y: 1,
+                width: 2,
+                height: 2
+            })
+        );
+        assert_eq!(buffer, [0xEE; 4]);
+    }
+}
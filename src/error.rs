@@ -1,6 +1,7 @@
 use crate::vendor::Driver;
 use std::fmt::{Debug, Display, Formatter};
 use crate::usb::UsbDevice;
+use crate::{ColorMode, Dithering, StorageMode};
 
 pub type Result<T, BACKEND> = std::result::Result<T, Error<BACKEND>>;
 pub type CoreResult<T> = std::result::Result<T, CoreError>;
@@ -28,6 +29,30 @@ pub enum CoreError {
     BusyScreen {
         screen_id: usize,
     },
+    InvalidPalette {
+        given: usize,
+        maximum: usize,
+    },
+    MissingPalette,
+    InvalidStride {
+        stride: usize,
+        row_length: usize,
+    },
+    AreaOutOfBounds {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+    UnsupportedColorMode {
+        mode: ColorMode,
+    },
+    UnsupportedStorage {
+        storage: StorageMode,
+    },
+    UnsupportedDithering {
+        dithering: Dithering,
+    },
 }
 
 impl<BACKEND : UsbDevice> std::error::Error for Error<BACKEND> {}
@@ -79,6 +104,32 @@ impl Display for CoreError {
                     "The screen with id {screen_id} is already used bay an other object"
                 )
             }
+            CoreError::InvalidPalette { given, maximum } => {
+                write!(f, "The palette must hold between 1 and {maximum} colors, given {given}")
+            }
+            CoreError::MissingPalette => {
+                write!(f, "An indexed color mode is used by a source without palette")
+            }
+            CoreError::InvalidStride { stride, row_length } => {
+                write!(f, "The stride must be at least {row_length}, given {stride}")
+            }
+            CoreError::AreaOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                write!(f, "The area of {width}x{height} at ({x}, {y}) is out of the frame")
+            }
+            CoreError::UnsupportedColorMode { mode } => {
+                write!(f, "The color mode {mode:?} is not supported by this conversion")
+            }
+            CoreError::UnsupportedStorage { storage } => {
+                write!(f, "The storage {storage:?} is not supported, a group must hold at most 16 bytes and between 1 and 64 bits per pixel")
+            }
+            CoreError::UnsupportedDithering { dithering } => {
+                write!(f, "The dithering {dithering:?} is not supported by this conversion")
+            }
         }
     }
 }
@@ -106,6 +157,29 @@ impl CoreError {
         }
     }
 
+    pub(crate) fn check_palette(length: usize, maximum: usize) -> CoreResult<()> {
+        if (1..=maximum).contains(&length) {
+            Ok(())
+        } else {
+            Err(CoreError::InvalidPalette {
+                given: length,
+                maximum,
+            })
+        }
+    }
+
+    /// Checks that a group of pixels holds at most 16 bytes, each pixel using between 1 and 64
+    /// bits.
+    pub(crate) fn check_storage(storage: StorageMode) -> CoreResult<()> {
+        let (pixels, bytes) = storage.group();
+
+        if bytes <= 16 && pixels > 0 && (1..=64).contains(&(8 * bytes / pixels)) {
+            Ok(())
+        } else {
+            Err(CoreError::UnsupportedStorage { storage })
+        }
+    }
+
     #[inline]
     pub(crate) fn throw_unsupported_device_error<'a, D: Driver<'a, DEV>, DEV : UsbDevice>(
         vendor_id: u16,
@@ -177,4 +251,24 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_check_palette() {
+        assert_eq!(CoreError::check_palette(1, 16), Ok(()));
+        assert_eq!(CoreError::check_palette(16, 16), Ok(()));
+        assert_eq!(
+            CoreError::check_palette(0, 16),
+            Err(CoreError::InvalidPalette {
+                given: 0,
+                maximum: 16
+            })
+        );
+        assert_eq!(
+            CoreError::check_palette(17, 16),
+            Err(CoreError::InvalidPalette {
+                given: 17,
+                maximum: 16
+            })
+        );
+    }
 }
@@ -57,11 +57,11 @@ mod test {
         const LENGTH: usize = WIDTH as usize * HEIGHT as usize;
 
         let buffer = crate::test_helper::DummyBuffer::new(COLOR_RED);
-        let mut iter = buffer.into_pixel_iter(10, 10, WIDTH, HEIGHT);
+        let iter = buffer.into_pixel_iter(10, 10, WIDTH, HEIGHT);
 
         let mut i = 0;
 
-        while let Some(_) = iter.next() {
+        for _ in iter {
             i += 1;
 
             if i > LENGTH {